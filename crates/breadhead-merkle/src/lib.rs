@@ -0,0 +1,141 @@
+//! Builds the mint allowlist merkle tree stored in `StakePool.authorization_merkle_root`
+//! and the proofs passed to `init_entry`.
+//!
+//! The staking program verifies proofs with `verify` from this crate: leaves are
+//! `keccak(mint)` and each pair of nodes is hashed in sorted order.
+
+use solana_program::{keccak, pubkey::Pubkey};
+
+pub fn leaf(mint: &Pubkey) -> [u8; 32] {
+    keccak::hashv(&[mint.as_ref()]).0
+}
+
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak::hashv(&[a, b]).0
+    } else {
+        keccak::hashv(&[b, a]).0
+    }
+}
+
+pub fn verify(proof: &[[u8; 32]], root: [u8; 32], mint: &Pubkey) -> bool {
+    proof.iter().fold(leaf(mint), |node, sibling| hash_pair(&node, sibling)) == root
+}
+
+pub struct MerkleTree {
+    // layers[0] holds the sorted leaves and the last layer holds the root
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(mints: &[Pubkey]) -> Self {
+        let mut leaves: Vec<[u8; 32]> = mints.iter().map(leaf).collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        let mut layers = vec![leaves];
+        while layers.last().unwrap().len() > 1 {
+            // an unpaired node is carried up to the next layer unchanged
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        MerkleTree { layers }
+    }
+
+    /// Root to store on the pool, or all zeroes for an empty tree.
+    pub fn root(&self) -> [u8; 32] {
+        self.layers.last().unwrap().first().copied().unwrap_or([0; 32])
+    }
+
+    /// Proof for `mint`, or `None` if the mint is not in the tree.
+    pub fn proof(&self, mint: &Pubkey) -> Option<Vec<[u8; 32]>> {
+        let mut index = self.layers[0].binary_search(&leaf(mint)).ok()?;
+        let mut proof = Vec::new();
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < layer.len() {
+                proof.push(layer[sibling]);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mints(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    fn assert_round_trip(mints: &[Pubkey]) {
+        let tree = MerkleTree::new(mints);
+        let root = tree.root();
+        for mint in mints {
+            let proof = tree.proof(mint).unwrap();
+            assert!(verify(&proof, root, mint));
+        }
+    }
+
+    #[test]
+    fn single_leaf_is_the_root() {
+        let mints = mints(1);
+        let tree = MerkleTree::new(&mints);
+        assert_eq!(tree.root(), leaf(&mints[0]));
+        assert!(tree.proof(&mints[0]).unwrap().is_empty());
+        assert_round_trip(&mints);
+    }
+
+    #[test]
+    fn even_leaf_counts_round_trip() {
+        for count in [2, 4, 8, 16] {
+            assert_round_trip(&mints(count));
+        }
+    }
+
+    #[test]
+    fn odd_leaf_counts_round_trip() {
+        for count in [3, 5, 7, 9, 13] {
+            assert_round_trip(&mints(count));
+        }
+    }
+
+    #[test]
+    fn duplicate_mints_are_deduplicated() {
+        let mut mints = mints(3);
+        mints.push(mints[0]);
+        let tree = MerkleTree::new(&mints);
+        assert_eq!(tree.root(), MerkleTree::new(&mints[..3]).root());
+        assert_round_trip(&mints);
+    }
+
+    #[test]
+    fn empty_tree_has_a_zero_root() {
+        let tree = MerkleTree::new(&[]);
+        assert_eq!(tree.root(), [0; 32]);
+        assert!(tree.proof(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn unlisted_mint_is_rejected() {
+        let mints = mints(5);
+        let tree = MerkleTree::new(&mints);
+        let unlisted = Pubkey::new_unique();
+        assert!(tree.proof(&unlisted).is_none());
+        // a proof only verifies the mint it was built for
+        let proof = tree.proof(&mints[0]).unwrap();
+        assert!(!verify(&proof, tree.root(), &unlisted));
+        assert!(!verify(&proof, tree.root(), &mints[1]));
+    }
+}
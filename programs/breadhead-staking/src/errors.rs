@@ -40,7 +40,7 @@ pub enum ErrorCode {
     InvalidAuthority,
     #[msg("Cannot close staked entry")]
     CannotCloseStakedEntry,
-    #[msg("Cannot close staked entry")]
+    #[msg("Cannot close pool with staked entries")]
     CannotClosePoolWithStakedEntries,
     #[msg("Token still has some cooldown seconds remaining")]
     CooldownSecondRemaining,
@@ -63,5 +63,59 @@ pub enum ErrorCode {
     #[msg("Invalid Master Edition account")]
     InvalidMasterEdition,
     #[msg("Invalid resting level")]
-    InvalidRestingLevel
+    InvalidRestingLevel,
+    #[msg("End date cannot be in the past while tokens are staked")]
+    InvalidEndDate,
+    #[msg("Invalid program config admins")]
+    InvalidAdmins,
+    #[msg("Signer is not the pending admin")]
+    InvalidPendingAdmin,
+    #[msg("Cannot remove the last program config admin")]
+    CannotRemoveLastAdmin,
+    #[msg("Rest levels must be non-empty, increasing and within the maximum count")]
+    InvalidRestLevels,
+    #[msg("Invalid achievement tier")]
+    InvalidAchievementTier,
+    #[msg("Invalid reward distributor kind")]
+    InvalidRewardDistributorKind,
+    #[msg("Invalid reward distributor")]
+    InvalidRewardDistributor,
+    #[msg("Invalid reward mint")]
+    InvalidRewardMint,
+    #[msg("Invalid reward distributor token account")]
+    InvalidRewardDistributorTokenAccount,
+    #[msg("Invalid user reward mint token account")]
+    InvalidUserRewardMintTokenAccount,
+    #[msg("Too many required collections")]
+    TooManyRequiredCollections,
+    #[msg("Invalid remaining accounts")]
+    InvalidRemainingAccounts,
+    #[msg("Invalid stake state")]
+    InvalidStakeState,
+    #[msg("Invalid progress mode")]
+    InvalidProgressMode,
+    #[msg("Invalid emergency unstake penalty")]
+    InvalidEmergencyPenalty,
+    #[msg("Invalid stake mode")]
+    InvalidStakeMode,
+    #[msg("Invalid escrow token account")]
+    InvalidEscrowTokenAccount,
+    #[msg("Invalid token record")]
+    InvalidTokenRecord,
+    #[msg("Invalid authorization rules")]
+    InvalidAuthorizationRules,
+    #[msg("Invalid instructions sysvar")]
+    InvalidInstructionsSysvar,
+    #[msg("Invalid stake amount")]
+    InvalidStakeAmount,
+    #[msg("Invalid unstake amount")]
+    InvalidUnstakeAmount,
+    #[msg("Stake pool has not ended")]
+    StakePoolHasNotEnded,
+    #[msg("Cannot close pool with an open reward distributor")]
+    CannotClosePoolWithRewardDistributor,
+    #[msg("Cannot increase stake or cooldown seconds while tokens are staked")]
+    CannotIncreaseLockWhileStaked,
+    #[msg("Cannot reassign an entry with a receipt mint or preserved stake progress")]
+    CannotReassignStakeEntry
 }
\ No newline at end of file
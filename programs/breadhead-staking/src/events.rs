@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PoolUpdated {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub old_requires_collections: Vec<Pubkey>,
+    pub new_requires_collections: Vec<Pubkey>,
+    pub old_requires_authorization: bool,
+    pub new_requires_authorization: bool,
+    pub old_authorization_merkle_root: Option<[u8; 32]>,
+    pub new_authorization_merkle_root: Option<[u8; 32]>,
+    pub old_reset_on_stake: bool,
+    pub new_reset_on_stake: bool,
+    pub old_cooldown_seconds: Option<u32>,
+    pub new_cooldown_seconds: Option<u32>,
+    pub old_min_stake_seconds: Option<u32>,
+    pub new_min_stake_seconds: Option<u32>,
+    pub old_end_date: Option<i64>,
+    pub new_end_date: Option<i64>,
+    pub old_rest_levels: Vec<i64>,
+    pub new_rest_levels: Vec<i64>,
+    pub old_progress_mode: u8,
+    pub new_progress_mode: u8,
+    pub old_progress_decay_levels: u8,
+    pub new_progress_decay_levels: u8,
+    pub old_emergency_forfeit_bps: u16,
+    pub new_emergency_forfeit_bps: u16,
+    pub old_emergency_level_drop: u8,
+    pub new_emergency_level_drop: u8,
+}
+
+#[event]
+pub struct Staked {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub staked_at: i64,
+    pub total_stake_seconds: u128,
+}
+
+#[event]
+pub struct UnstakeCooldownStarted {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub cooldown_start: i64,
+    pub total_stake_seconds: u128,
+}
+
+#[event]
+pub struct Unstaked {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub unstaked_at: i64,
+    pub total_stake_seconds: u128,
+}
+
+#[event]
+pub struct EmergencyUnstaked {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub unstaked_at: i64,
+    pub forfeited_stake_seconds: u128,
+    pub total_stake_seconds: u128,
+    pub old_resting_level: u8,
+    pub new_resting_level: u8,
+}
+
+#[event]
+pub struct RestingLevelChanged {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub stake_start: i64,
+    pub amount: u64,
+    // accrued stake seconds including the running stake
+    pub total_stake_seconds: u128,
+    pub old_resting_level: u8,
+    pub new_resting_level: u8,
+    pub achievment_level: Option<u8>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EntrySettled {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub settled_stake_seconds: u128,
+    pub total_stake_seconds: u128,
+    pub end_date: i64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub reward_seconds: u128,
+    pub claimed_at: i64,
+}
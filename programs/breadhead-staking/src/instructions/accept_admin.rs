@@ -0,0 +1,34 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<AcceptAdminCtx>) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    let new_admin = ctx.accounts.pending_admin.key();
+    let proposer = match program_config.pending_admin_proposer {
+        Some(proposer) => proposer,
+        None => return Err(error!(ErrorCode::InvalidPendingAdmin)),
+    };
+
+    // the transfer replaces the proposer instead of adding a seat
+    program_config.admins.retain(|key| key != &proposer && key != &new_admin);
+    program_config.admins.push(new_admin);
+    program_config.pending_admin = None;
+    program_config.pending_admin_proposer = None;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdminCtx<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = program_config.pending_admin == Some(pending_admin.key())
+        @ ErrorCode::InvalidPendingAdmin
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub pending_admin: Signer<'info>,
+}
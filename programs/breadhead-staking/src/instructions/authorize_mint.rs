@@ -0,0 +1,34 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<AuthorizeMintCtx>, mint: Pubkey) -> Result<()> {
+    let stake_authorization_record = &mut ctx.accounts.stake_authorization_record;
+    stake_authorization_record.bump = *ctx.bumps.get("stake_authorization_record").unwrap();
+    stake_authorization_record.pool = ctx.accounts.stake_pool.key();
+    stake_authorization_record.mint = mint;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct AuthorizeMintCtx<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = STAKE_AUTHORIZATION_SIZE,
+        seeds = [STAKE_AUTHORIZATION_PREFIX.as_bytes(), stake_pool.key().as_ref(), mint.as_ref()],
+        bump
+    )]
+    pub stake_authorization_record: Account<'info, StakeAuthorizationRecord>,
+    #[account(
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
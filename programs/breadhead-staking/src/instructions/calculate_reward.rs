@@ -1,55 +1,82 @@
 use {
-    crate::{errors::ErrorCode, state::*},
+    crate::{errors::ErrorCode, events::RestingLevelChanged, state::*},
     anchor_lang::prelude::*,
     anchor_spl::token::{Mint, TokenAccount},
 };
 
 pub fn handler(ctx: Context<RewardCtx>) -> Result<()> {
     let user_state = &mut ctx.accounts.stake_state;
+    let old_resting_level = refresh_levels(&ctx.accounts.stake_pool, user_state, ctx.remaining_accounts)?;
+    ctx.accounts.staker_profile.record_levels(user_state.resting_level, user_state.achievment_level);
 
-    let user_resting_level = derive_resting_level(user_state.stake_start);
-    user_state.resting_level = user_resting_level;
+    if old_resting_level != user_state.resting_level {
+        ctx.accounts.pool_stats.record_level_change(old_resting_level, user_state.resting_level);
+        let stake_entry = &ctx.accounts.stake_entry;
+        let now = Clock::get().unwrap().unix_timestamp;
+        emit!(RestingLevelChanged {
+            pool: ctx.accounts.stake_pool.key(),
+            mint: ctx.accounts.original_mint.key(),
+            user: ctx.accounts.user.key(),
+            stake_start: user_state.stake_start,
+            amount: stake_entry.amount,
+            total_stake_seconds: stake_entry.total_stake_seconds.saturating_add(stake_entry.pending_stake_seconds(now, ctx.accounts.stake_pool.end_date)),
+            old_resting_level,
+            new_resting_level: user_state.resting_level,
+            achievment_level: user_state.achievment_level,
+            timestamp: now,
+        });
+    }
 
-    msg!("user state rest level: {}", user_state.resting_level);
+    Ok(())
+}
 
-    match user_state.resting_level {
-        0 => user_state.achievment_level = Achievement::DoughBoy,
-        1 => user_state.achievment_level = Achievement::SixtyNineBadge,
-        2 => user_state.achievment_level = Achievement::BagHolder,
-        3 => user_state.achievment_level = Achievement::MoonShot,
-        4 => user_state.achievment_level = Achievement::Loaf,
-        5 => user_state.achievment_level = Achievement::BreadGetter,
-        _ => return Err(error!(ErrorCode::InvalidRestingLevel))
-    }
+// recompute resting and achievement levels from the pool config, returns the previous resting level
+pub fn refresh_levels(stake_pool: &Account<StakePool>, stake_state: &mut StakeState, tier_infos: &[AccountInfo]) -> Result<u8> {
+    let old_resting_level = stake_state.resting_level;
 
-    msg!("user achievement level: {:?}", user_state.achievment_level);
+    stake_state.resting_level = derive_resting_level(stake_state.stake_start, &stake_pool.rest_levels, stake_pool.end_date);
+    stake_state.achievment_level = derive_achievement_level(
+        &stake_pool.key(),
+        stake_pool.achievement_tier_count,
+        stake_state.resting_level,
+        tier_infos,
+    )?;
 
-    Ok(())
+    Ok(old_resting_level)
 }
 
 #[derive(Accounts)]
 pub struct RewardCtx<'info> {
+    #[account(constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
     #[account(mut, seeds = [STAKE_ENTRY_PREFIX.as_bytes(), stake_entry.pool.as_ref(), stake_entry.original_mint.as_ref(), get_stake_seed(original_mint.supply, user.key()).as_ref()], bump=stake_entry.bump)]
     pub stake_entry: Box<Account<'info, StakeEntry>>,
 
     // user
-    #[account(mut)]
+    #[account(mut, constraint = user.key() == stake_entry.last_staker @ ErrorCode::InvalidStakeEntry)]
     pub user: Signer<'info>,
     #[account(
         mut,
-        constraint = user_original_mint_token_account.amount > 0
-        && user_original_mint_token_account.mint == stake_entry.original_mint
-        && user_original_mint_token_account.owner == user.key()
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), user.key().as_ref()],
+        bump = staker_profile.bump
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
+    #[account(
+        mut,
+        constraint = holds_staked_token(&stake_pool, &stake_entry, &user_original_mint_token_account, &user.key())
         @ ErrorCode::InvalidUserOriginalMintTokenAccount
     )]
     pub user_original_mint_token_account: Box<Account<'info, TokenAccount>>,
     pub original_mint: Box<Account<'info, Mint>>,
     #[account(
         mut,
-        seeds = [user.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
+        seeds = [user.key().as_ref(), stake_pool.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
         bump = stake_state.bump,
         constraint = stake_state.token_account == user_original_mint_token_account.key()
         @ ErrorCode::InvalidStakeEntryOriginalMintTokenAccount,
+        constraint = stake_state.pool == stake_pool.key() @ ErrorCode::InvalidStakePool
     )]
     pub stake_state: Account<'info, StakeState>,
 }
\ No newline at end of file
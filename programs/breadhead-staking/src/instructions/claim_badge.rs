@@ -0,0 +1,218 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token::AssociatedToken,
+        token::{Mint, Token, TokenAccount, Approve, approve, MintTo, mint_to},
+    },
+    mpl_token_metadata::{
+        instruction::{create_metadata_accounts_v3, create_master_edition_v3, freeze_delegated_account},
+        state::Creator,
+        ID as metadata_program_id,
+    },
+    solana_program::program::invoke_signed
+};
+
+pub fn handler(ctx: Context<ClaimBadgeCtx>) -> Result<()> {
+    let auth_bump = *ctx.bumps.get("program_authority").unwrap();
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+
+    // mint the badge to the holder
+    mint_to(ctx.accounts.mint_to_ctx().with_signer(signer), 1)?;
+
+    // badge metadata comes from the achievement tier
+    let metadata_ix = create_metadata_accounts_v3(
+        ctx.accounts.metadata_program.key(),
+        ctx.accounts.badge_metadata.key(),
+        ctx.accounts.badge_mint.key(),
+        ctx.accounts.program_authority.key(),
+        ctx.accounts.user.key(),
+        ctx.accounts.program_authority.key(),
+        ctx.accounts.achievement_tier.label.clone(),
+        BADGE_SYMBOL.to_string(),
+        ctx.accounts.achievement_tier.uri.clone(),
+        Some(vec![Creator {
+            address: ctx.accounts.program_authority.key(),
+            verified: true,
+            share: 100,
+        }]),
+        0,
+        true,
+        false,
+        None,
+        None,
+        None
+    );
+    invoke_signed(
+        &metadata_ix,
+        &[
+            ctx.accounts.metadata_program.to_account_info(),
+            ctx.accounts.badge_metadata.to_account_info(),
+            ctx.accounts.badge_mint.to_account_info(),
+            ctx.accounts.program_authority.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info()
+        ],
+        signer
+    )?;
+
+    // master edition with no prints makes the badge a one of one
+    let master_edition_ix = create_master_edition_v3(
+        ctx.accounts.metadata_program.key(),
+        ctx.accounts.badge_master_edition.key(),
+        ctx.accounts.badge_mint.key(),
+        ctx.accounts.program_authority.key(),
+        ctx.accounts.program_authority.key(),
+        ctx.accounts.badge_metadata.key(),
+        ctx.accounts.user.key(),
+        Some(0)
+    );
+    invoke_signed(
+        &master_edition_ix,
+        &[
+            ctx.accounts.metadata_program.to_account_info(),
+            ctx.accounts.badge_master_edition.to_account_info(),
+            ctx.accounts.badge_mint.to_account_info(),
+            ctx.accounts.program_authority.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.badge_metadata.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info()
+        ],
+        signer
+    )?;
+
+    // freeze the badge in the holder's wallet so it cannot be transferred
+    approve(ctx.accounts.approve_ctx(), 1)?;
+    let freeze_ix = freeze_delegated_account(
+        ctx.accounts.metadata_program.key(),
+        ctx.accounts.program_authority.key(),
+        ctx.accounts.user_badge_token_account.key(),
+        ctx.accounts.badge_master_edition.key(),
+        ctx.accounts.badge_mint.key()
+    );
+    invoke_signed(
+        &freeze_ix,
+        &[
+            ctx.accounts.metadata_program.to_account_info(),
+            ctx.accounts.program_authority.to_account_info(),
+            ctx.accounts.user_badge_token_account.to_account_info(),
+            ctx.accounts.badge_master_edition.to_account_info(),
+            ctx.accounts.badge_mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info()
+        ],
+        signer
+    )?;
+
+    let badge_record = &mut ctx.accounts.badge_record;
+    badge_record.bump = *ctx.bumps.get("badge_record").unwrap();
+    badge_record.pool = ctx.accounts.stake_pool.key();
+    badge_record.user = ctx.accounts.user.key();
+    badge_record.tier = ctx.accounts.achievement_tier.index;
+    badge_record.mint = ctx.accounts.badge_mint.key();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimBadgeCtx<'info> {
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(
+        seeds = [user.key().as_ref(), stake_pool.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
+        bump = stake_state.bump,
+        constraint = stake_state.pool == stake_pool.key() @ ErrorCode::InvalidStakePool
+    )]
+    pub stake_state: Box<Account<'info, StakeState>>,
+    pub original_mint: Box<Account<'info, Mint>>,
+    #[account(
+        constraint = achievement_tier.pool == stake_pool.key()
+        && stake_state.achievment_level == Some(achievement_tier.index)
+        && achievement_tier.threshold <= stake_state.resting_level
+        @ ErrorCode::InvalidAchievementTier
+    )]
+    pub achievement_tier: Box<Account<'info, AchievementTier>>,
+
+    // one badge per tier per wallet
+    #[account(
+        init,
+        payer = user,
+        space = BADGE_RECORD_SIZE,
+        seeds = [BADGE_RECORD_PREFIX.as_bytes(), stake_pool.key().as_ref(), &[achievement_tier.index], user.key().as_ref()],
+        bump
+    )]
+    pub badge_record: Box<Account<'info, BadgeRecord>>,
+    #[account(
+        init,
+        payer = user,
+        seeds = [BADGE_MINT_PREFIX.as_bytes(), badge_record.key().as_ref()],
+        bump,
+        mint::decimals = 0,
+        mint::authority = program_authority,
+        mint::freeze_authority = program_authority
+    )]
+    pub badge_mint: Box<Account<'info, Mint>>,
+    /// CHECK: metadata program verifies the derivation when creating the metadata
+    #[account(mut)]
+    pub badge_metadata: AccountInfo<'info>,
+    /// CHECK: metadata program verifies the derivation when creating the master edition
+    #[account(mut)]
+    pub badge_master_edition: AccountInfo<'info>,
+
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        mut,
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    // user
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = badge_mint,
+        associated_token::authority = user
+    )]
+    pub user_badge_token_account: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: constraint verifies this is the metadata program
+    #[account(constraint =
+        metadata_program.key() == metadata_program_id
+        @ ErrorCode::InvalidMetadataProgram
+    )]
+    pub metadata_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>
+}
+
+impl<'info> ClaimBadgeCtx <'info> {
+    pub fn mint_to_ctx(&self) -> CpiContext<'_,'_,'_, 'info, MintTo<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = MintTo {
+            mint: self.badge_mint.to_account_info(),
+            to: self.user_badge_token_account.to_account_info(),
+            authority: self.program_authority.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn approve_ctx(&self) -> CpiContext<'_,'_,'_, 'info, Approve<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = Approve {
+            to: self.user_badge_token_account.to_account_info(),
+            delegate: self.program_authority.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
@@ -0,0 +1,139 @@
+use {
+    crate::{errors::ErrorCode, events::RewardsClaimed, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount, MintTo, mint_to, Transfer, transfer},
+};
+
+pub fn handler(ctx: Context<ClaimRewardsCtx>) -> Result<()> {
+    let stake_entry = &ctx.accounts.stake_entry;
+    let reward_distributor = &ctx.accounts.reward_distributor;
+
+    // seconds accrued by the entry that have not been paid out yet
+    let total_stake_seconds = stake_entry.total_stake_seconds.saturating_add(
+        stake_entry.pending_stake_seconds(Clock::get().unwrap().unix_timestamp, ctx.accounts.stake_pool.end_date)
+    );
+    let reward_seconds = total_stake_seconds.saturating_sub(stake_entry.reward_seconds_claimed);
+
+    let mut reward_amount = reward_seconds
+        .checked_mul(u128::from(reward_distributor.reward_rate))
+        .unwrap()
+        .min(u128::from(u64::MAX));
+    if let Some(max_supply) = reward_distributor.max_supply {
+        reward_amount = reward_amount.min(u128::from(max_supply.saturating_sub(reward_distributor.rewards_issued)));
+    }
+    let reward_amount = u64::try_from(reward_amount).unwrap();
+
+    // only mark the seconds actually paid for so capped payouts are not lost
+    let reward_seconds_paid = if reward_distributor.reward_rate == 0 {
+        reward_seconds
+    } else {
+        u128::from(reward_amount) / u128::from(reward_distributor.reward_rate)
+    };
+
+    if reward_amount > 0 {
+        let stake_pool_key = ctx.accounts.stake_pool.key();
+        let distributor_seeds = &[REWARD_DISTRIBUTOR_PREFIX.as_bytes(), stake_pool_key.as_ref(), &[reward_distributor.bump]];
+        let signer = &[&distributor_seeds[..]];
+
+        match reward_distributor.kind {
+            k if k == RewardDistributorKind::Mint as u8 => {
+                mint_to(ctx.accounts.mint_to_ctx().with_signer(signer), reward_amount)?;
+            }
+            k if k == RewardDistributorKind::Treasury as u8 => {
+                transfer(ctx.accounts.transfer_ctx().with_signer(signer), reward_amount)?;
+            }
+            _ => return Err(error!(ErrorCode::InvalidRewardDistributorKind)),
+        }
+    }
+
+    let reward_distributor = &mut ctx.accounts.reward_distributor;
+    reward_distributor.rewards_issued = reward_distributor.rewards_issued.checked_add(reward_amount).unwrap();
+
+    let stake_entry = &mut ctx.accounts.stake_entry;
+    stake_entry.reward_seconds_claimed = stake_entry.reward_seconds_claimed.checked_add(reward_seconds_paid).unwrap();
+
+    emit!(RewardsClaimed {
+        pool: ctx.accounts.stake_pool.key(),
+        mint: ctx.accounts.stake_entry.original_mint,
+        user: ctx.accounts.user.key(),
+        reward_mint: ctx.accounts.reward_mint.key(),
+        amount: reward_amount,
+        reward_seconds: reward_seconds_paid,
+        claimed_at: Clock::get().unwrap().unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardsCtx<'info> {
+    #[account(
+        mut,
+        seeds = [REWARD_DISTRIBUTOR_PREFIX.as_bytes(), stake_pool.key().as_ref()],
+        bump = reward_distributor.bump
+    )]
+    pub reward_distributor: Box<Account<'info, RewardDistributor>>,
+    #[account(
+        mut,
+        constraint = reward_distributor_token_account.mint == reward_distributor.reward_mint
+        && reward_distributor_token_account.owner == reward_distributor.key()
+        @ ErrorCode::InvalidRewardDistributorTokenAccount
+    )]
+    pub reward_distributor_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = reward_mint.key() == reward_distributor.reward_mint
+        @ ErrorCode::InvalidRewardMint
+    )]
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    #[account(constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    // fungible entries are keyed per user so only their owner can claim them
+    #[account(mut, seeds = [STAKE_ENTRY_PREFIX.as_bytes(), stake_entry.pool.as_ref(), stake_entry.original_mint.as_ref(), get_stake_seed(original_mint.supply, user.key()).as_ref()], bump=stake_entry.bump)]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    #[account(constraint = original_mint.key() == stake_entry.original_mint @ ErrorCode::InvalidOriginalMint)]
+    pub original_mint: Box<Account<'info, Mint>>,
+
+    // user
+    pub user: Signer<'info>,
+    #[account(
+        constraint = holds_staked_token(&stake_pool, &stake_entry, &user_original_mint_token_account, &user.key())
+        @ ErrorCode::InvalidUserOriginalMintTokenAccount
+    )]
+    pub user_original_mint_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = user_reward_mint_token_account.mint == reward_distributor.reward_mint
+        && user_reward_mint_token_account.owner == user.key()
+        @ ErrorCode::InvalidUserRewardMintTokenAccount
+    )]
+    pub user_reward_mint_token_account: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimRewardsCtx <'info> {
+    pub fn mint_to_ctx(&self) -> CpiContext<'_,'_,'_, 'info, MintTo<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = MintTo {
+            mint: self.reward_mint.to_account_info(),
+            to: self.user_reward_mint_token_account.to_account_info(),
+            authority: self.reward_distributor.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn transfer_ctx(&self) -> CpiContext<'_,'_,'_, 'info, Transfer<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.reward_distributor_token_account.to_account_info(),
+            to: self.user_reward_mint_token_account.to_account_info(),
+            authority: self.reward_distributor.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
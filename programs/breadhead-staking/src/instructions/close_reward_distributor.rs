@@ -0,0 +1,105 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount, SetAuthority, set_authority, Transfer, transfer, CloseAccount, close_account},
+    spl_token::instruction::AuthorityType,
+};
+
+pub fn handler(ctx: Context<CloseRewardDistributorCtx>) -> Result<()> {
+    let stake_pool_key = ctx.accounts.stake_pool.key();
+    let distributor_seeds = &[REWARD_DISTRIBUTOR_PREFIX.as_bytes(), stake_pool_key.as_ref(), &[ctx.accounts.reward_distributor.bump]];
+    let signer = &[&distributor_seeds[..]];
+
+    match ctx.accounts.reward_distributor.kind {
+        k if k == RewardDistributorKind::Mint as u8 => {
+            // hand mint authority back to the distributor authority
+            set_authority(ctx.accounts.set_authority_ctx().with_signer(signer), AuthorityType::MintTokens, Some(ctx.accounts.authority.key()))?;
+        }
+        k if k == RewardDistributorKind::Treasury as u8 => {}
+        _ => return Err(error!(ErrorCode::InvalidRewardDistributorKind)),
+    }
+
+    // withdraw the unpaid rewards and close the distributor token account
+    let remaining_rewards = ctx.accounts.reward_distributor_token_account.amount;
+    if remaining_rewards > 0 {
+        transfer(ctx.accounts.transfer_ctx().with_signer(signer), remaining_rewards)?;
+    }
+    close_account(ctx.accounts.close_account_ctx().with_signer(signer))?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseRewardDistributorCtx<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [REWARD_DISTRIBUTOR_PREFIX.as_bytes(), stake_pool.key().as_ref()],
+        bump = reward_distributor.bump,
+        constraint = reward_distributor.authority == authority.key()
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub reward_distributor: Box<Account<'info, RewardDistributor>>,
+    #[account(
+        mut,
+        constraint = reward_distributor_token_account.mint == reward_distributor.reward_mint
+        && reward_distributor_token_account.owner == reward_distributor.key()
+        @ ErrorCode::InvalidRewardDistributorTokenAccount
+    )]
+    pub reward_distributor_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = reward_mint.key() == reward_distributor.reward_mint
+        @ ErrorCode::InvalidRewardMint
+    )]
+    pub reward_mint: Box<Account<'info, Mint>>,
+    pub stake_pool: Box<Account<'info, StakePool>>,
+
+    // authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = authority_reward_mint_token_account.mint == reward_distributor.reward_mint
+        && authority_reward_mint_token_account.owner == authority.key()
+        @ ErrorCode::InvalidUserRewardMintTokenAccount
+    )]
+    pub authority_reward_mint_token_account: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CloseRewardDistributorCtx <'info> {
+    pub fn set_authority_ctx(&self) -> CpiContext<'_,'_,'_, 'info, SetAuthority<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = SetAuthority {
+            account_or_mint: self.reward_mint.to_account_info(),
+            current_authority: self.reward_distributor.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn transfer_ctx(&self) -> CpiContext<'_,'_,'_, 'info, Transfer<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.reward_distributor_token_account.to_account_info(),
+            to: self.authority_reward_mint_token_account.to_account_info(),
+            authority: self.reward_distributor.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_account_ctx(&self) -> CpiContext<'_,'_,'_, 'info, CloseAccount<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = CloseAccount {
+            account: self.reward_distributor_token_account.to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.reward_distributor.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
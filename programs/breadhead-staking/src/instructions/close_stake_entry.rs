@@ -0,0 +1,62 @@
+use {
+    crate::{errors::ErrorCode, state::*, utils::close_escrow},
+    anchor_lang::prelude::*,
+    anchor_spl::token::Token,
+};
+
+pub fn handler(ctx: Context<CloseStakeEntryCtx>) -> Result<()> {
+    // the escrow is seeded by the entry and would be orphaned once it is closed
+    if ctx.accounts.stake_pool.stake_mode == StakeMode::Escrow as u8 && !ctx.accounts.stake_escrow.data_is_empty() {
+        close_escrow(
+            &ctx.accounts.stake_escrow,
+            &ctx.accounts.payer,
+            &ctx.accounts.program_authority,
+            &ctx.accounts.token_program.to_account_info(),
+            *ctx.bumps.get("program_authority").unwrap(),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseStakeEntryCtx<'info> {
+    #[account(
+        mut,
+        close = payer,
+        constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool,
+        constraint = stake_entry.amount == 0 @ ErrorCode::CannotCloseStakedEntry
+    )]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    /// CHECK: only created in escrow mode, closed in the handler if it exists
+    #[account(
+        mut,
+        seeds = [STAKE_ESCROW_PREFIX.as_bytes(), stake_entry.key().as_ref()],
+        bump
+    )]
+    pub stake_escrow: AccountInfo<'info>,
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    #[account(
+        constraint = authority.key() == stake_pool.authority
+        || authority.key() == stake_entry.payer
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+    /// CHECK: constraint verifies this is the account that paid for the stake entry
+    #[account(
+        mut,
+        constraint = payer.key() == stake_entry.payer
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub payer: AccountInfo<'info>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,38 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(_ctx: Context<CloseStakePoolCtx>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseStakePoolCtx<'info> {
+    #[account(
+        mut,
+        close = authority,
+        constraint = stake_pool.total_staked == 0
+        @ ErrorCode::CannotClosePoolWithStakedEntries,
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()],
+        bump = pool_stats.bump
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
+    /// CHECK: the distributor reads the pool to pay out, it has to be closed first
+    #[account(
+        seeds = [REWARD_DISTRIBUTOR_PREFIX.as_bytes(), stake_pool.key().as_ref()],
+        bump,
+        constraint = reward_distributor.data_is_empty()
+        @ ErrorCode::CannotClosePoolWithRewardDistributor
+    )]
+    pub reward_distributor: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
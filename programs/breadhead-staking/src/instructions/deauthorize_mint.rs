@@ -0,0 +1,28 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(_ctx: Context<DeauthorizeMintCtx>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DeauthorizeMintCtx<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [STAKE_AUTHORIZATION_PREFIX.as_bytes(), stake_pool.key().as_ref(), stake_authorization_record.mint.as_ref()],
+        bump = stake_authorization_record.bump,
+        constraint = stake_authorization_record.pool == stake_pool.key()
+        @ ErrorCode::InvalidStakeAuthorizationRecord
+    )]
+    pub stake_authorization_record: Account<'info, StakeAuthorizationRecord>,
+    #[account(
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
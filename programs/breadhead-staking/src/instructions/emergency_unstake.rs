@@ -0,0 +1,221 @@
+use {
+    crate::{
+        errors::ErrorCode,
+        events::EmergencyUnstaked,
+        instructions::unstake::{apply_progress_mode, record_unstake},
+        state::*,
+        utils::{burn_receipt, is_programmable, load_original_mint_metadata, thaw_token_account, transfer_from_escrow, unlock_programmable, validate_escrow_token_account, ProgrammableAccounts}
+    },
+    anchor_lang::prelude::*,
+    anchor_lang::AccountsClose,
+    anchor_spl::token::{Mint, Token, TokenAccount, Revoke, revoke},
+    mpl_token_metadata::{
+        ID as metadata_program_id,
+        utils::is_master_edition
+    },
+    solana_program::sysvar::instructions::ID as sysvar_instructions_id,
+};
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, EmergencyUnstakeCtx<'info>>) -> Result<()> {
+    // skips min stake seconds and cooldown, the pool penalty is applied instead
+
+    // the whole entry is unstaked
+    let amount = ctx.accounts.stake_entry.amount;
+
+    // burn the receipts for the staked position
+    burn_receipt(
+        &mut ctx.accounts.stake_entry,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        amount
+    )?;
+
+    if ctx.accounts.stake_pool.stake_mode == StakeMode::Escrow as u8 {
+        // return the tokens from escrow to the staker
+        validate_escrow_token_account(&ctx.accounts.escrow_token_account, &ctx.accounts.stake_entry.key(), &ctx.accounts.original_mint.key())?;
+        transfer_from_escrow(
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.user_original_mint_token_account.to_account_info(),
+            &ctx.accounts.program_authority,
+            &ctx.accounts.token_program.to_account_info(),
+            *ctx.bumps.get("program_authority").unwrap(),
+            amount
+        )?;
+    } else {
+        let original_mint_metadata = load_original_mint_metadata(&ctx.accounts.original_mint_metadata, &ctx.accounts.original_mint.key())?;
+        if is_programmable(&original_mint_metadata) {
+            // unlock and revoke the staking delegate through the metadata program
+            unlock_programmable(&ctx.accounts.programmable_accounts(), &original_mint_metadata, *ctx.bumps.get("program_authority").unwrap())?;
+        } else {
+            // thaw token account
+            thaw_token_account(
+                &ctx.accounts.metadata_program,
+                &ctx.accounts.program_authority,
+                &ctx.accounts.user_original_mint_token_account.to_account_info(),
+                &ctx.accounts.master_edition,
+                &ctx.accounts.original_mint.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                *ctx.bumps.get("program_authority").unwrap()
+            )?;
+
+            // revoke program authority as delegate
+            revoke(ctx.accounts.revoke_ctx())?;
+        }
+    }
+
+    let stake_seconds = record_unstake(&mut ctx.accounts.stake_pool, &mut ctx.accounts.stake_entry, amount);
+    ctx.accounts.pool_stats.record_unstake(&mut ctx.accounts.staker_profile, stake_seconds, ctx.accounts.stake_state.resting_level);
+    ctx.accounts.staker_profile.record_stake_seconds(stake_seconds);
+
+    // forfeit a share of the entry stake seconds
+    let stake_entry = &mut ctx.accounts.stake_entry;
+    let forfeited_stake_seconds = stake_entry.total_stake_seconds
+        .checked_mul(u128::from(ctx.accounts.stake_pool.emergency_forfeit_bps))
+        .unwrap()
+        / u128::from(MAX_BPS);
+    stake_entry.total_stake_seconds = stake_entry.total_stake_seconds.saturating_sub(forfeited_stake_seconds);
+    // forfeited seconds that were already paid are not paid again once the entry accrues past them
+    stake_entry.reward_seconds_claimed = stake_entry.reward_seconds_claimed.min(stake_entry.total_stake_seconds);
+    // the stats were credited with the full stake seconds by record_unstake
+    ctx.accounts.pool_stats.forfeit_stake_seconds(forfeited_stake_seconds);
+    ctx.accounts.staker_profile.forfeit_stake_seconds(forfeited_stake_seconds);
+
+    // drop resting levels before the pool progress mode is applied
+    let stake_state = &mut ctx.accounts.stake_state;
+    let old_resting_level = stake_state.resting_level;
+    stake_state.resting_level = stake_state.resting_level.saturating_sub(ctx.accounts.stake_pool.emergency_level_drop);
+    if stake_state.resting_level != old_resting_level {
+        // the tier reached at the old level no longer applies, recomputed on the next calculate_reward
+        stake_state.achievment_level = None;
+    }
+    let close_stake_state = apply_progress_mode(&ctx.accounts.stake_pool, stake_state);
+
+    emit!(EmergencyUnstaked {
+        pool: ctx.accounts.stake_pool.key(),
+        mint: ctx.accounts.original_mint.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        unstaked_at: Clock::get().unwrap().unix_timestamp,
+        forfeited_stake_seconds,
+        total_stake_seconds: ctx.accounts.stake_entry.total_stake_seconds,
+        old_resting_level,
+        new_resting_level: ctx.accounts.stake_state.resting_level,
+    });
+
+    // close user stake state account unless the pool keeps progress
+    if close_stake_state {
+        ctx.accounts.stake_state.close(ctx.accounts.user.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct EmergencyUnstakeCtx<'info> {
+    #[account(mut, constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
+    #[account(mut, seeds = [STAKE_ENTRY_PREFIX.as_bytes(), stake_entry.pool.as_ref(), stake_entry.original_mint.as_ref(), get_stake_seed(original_mint.supply, user.key()).as_ref()], bump=stake_entry.bump)]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        mut,
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+    pub original_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: constraint verifies this is a master edition, fungible mints have none
+    #[account(constraint = 
+        original_mint.supply > 1 || is_master_edition(
+            &master_edition, original_mint.decimals, original_mint.supply)
+            @ ErrorCode::InvalidMasterEdition
+        )]
+        pub master_edition: AccountInfo<'info>,
+    /// CHECK: derivation and owner verified in the handler
+    #[account(mut)]
+    pub original_mint_metadata: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts, verified against the token account in the handler
+    #[account(mut)]
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts with a rule set, verified against the metadata in the handler
+    pub authorization_rules: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts with a rule set, verified by the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
+
+    // user
+    #[account(mut, constraint = user.key() == stake_entry.last_staker @ ErrorCode::InvalidUnstakeUser)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), user.key().as_ref()],
+        bump = staker_profile.bump
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
+    #[account(
+        mut,
+        constraint = user_original_mint_token_account.mint == stake_entry.original_mint
+        && user_original_mint_token_account.owner == user.key()
+        @ ErrorCode::InvalidUserOriginalMintTokenAccount)]
+        pub user_original_mint_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: only used in escrow mode, verified against the stake entry in the handler
+    #[account(mut)]
+    pub escrow_token_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [user.key().as_ref(), stake_pool.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
+        bump = stake_state.bump,
+        constraint = stake_state.pool == stake_pool.key() @ ErrorCode::InvalidStakePool
+    )]
+    pub stake_state: Account<'info, StakeState>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+    /// CHECK: constraint verifies this is the metadata program
+    #[account(
+        constraint = metadata_program.key() == metadata_program_id
+        @ ErrorCode::InvalidMetadataProgram
+    )]
+    pub metadata_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: constraint verifies this is the instructions sysvar
+    #[account(
+        constraint = sysvar_instructions.key() == sysvar_instructions_id
+        @ ErrorCode::InvalidInstructionsSysvar
+    )]
+    pub sysvar_instructions: AccountInfo<'info>
+}
+
+impl<'info> EmergencyUnstakeCtx <'info> {
+    pub fn revoke_ctx(&self) -> CpiContext<'_,'_,'_, 'info, Revoke<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = Revoke {
+            source: self.user_original_mint_token_account.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn programmable_accounts(&self) -> ProgrammableAccounts<'info> {
+        ProgrammableAccounts {
+            metadata_program: self.metadata_program.to_account_info(),
+            metadata: self.original_mint_metadata.to_account_info(),
+            master_edition: self.master_edition.to_account_info(),
+            token_record: self.token_record.to_account_info(),
+            mint: self.original_mint.to_account_info(),
+            token_account: self.user_original_mint_token_account.to_account_info(),
+            user: self.user.to_account_info(),
+            program_authority: self.program_authority.to_account_info(),
+            authorization_rules_program: self.authorization_rules_program.to_account_info(),
+            authorization_rules: self.authorization_rules.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            sysvar_instructions: self.sysvar_instructions.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<InitAchievementTierCtx>, ix: InitAchievementTierIx) -> Result<()> {
+    validate_achievement_tier(ix.threshold, &ix.label, &ix.uri)?;
+
+    let stake_pool = &mut ctx.accounts.stake_pool;
+    let achievement_tier = &mut ctx.accounts.achievement_tier;
+    achievement_tier.bump = *ctx.bumps.get("achievement_tier").unwrap();
+    achievement_tier.pool = stake_pool.key();
+    achievement_tier.index = stake_pool.achievement_tier_count;
+    achievement_tier.threshold = ix.threshold;
+    achievement_tier.label = ix.label;
+    achievement_tier.uri = ix.uri;
+
+    stake_pool.achievement_tier_count = stake_pool.achievement_tier_count.checked_add(1).unwrap();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitAchievementTierCtx<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ACHIEVEMENT_TIER_SIZE,
+        seeds = [ACHIEVEMENT_TIER_PREFIX.as_bytes(), stake_pool.key().as_ref(), &[stake_pool.achievement_tier_count]],
+        bump
+    )]
+    pub achievement_tier: Account<'info, AchievementTier>,
+    #[account(
+        mut,
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitAchievementTierIx {
+    threshold: u8,
+    label: String,
+    uri: String,
+}
@@ -1,19 +1,19 @@
 use mpl_token_metadata::utils::assert_derivation;
 use {
-    crate::{errors::ErrorCode, state::*},
+    crate::{errors::ErrorCode, state::*, utils::load_original_mint_metadata},
     anchor_lang::prelude::*,
     anchor_spl::token::Mint,
-    mpl_token_metadata::state::Metadata,
     mpl_token_metadata::{self},
 };
 
-pub fn handler(ctx: Context<InitEntryCtx>, _user: Pubkey) -> Result<()> {
+pub fn handler(ctx: Context<InitEntryCtx>, _user: Pubkey, authorization_proof: Option<Vec<[u8; 32]>>) -> Result<()> {
     let stake_entry = &mut ctx.accounts.stake_entry;
     let stake_pool = &ctx.accounts.stake_pool;
     stake_entry.bump = *ctx.bumps.get("stake_entry").unwrap();
     stake_entry.pool = ctx.accounts.stake_pool.key();
     stake_entry.original_mint = ctx.accounts.original_mint.key();
     stake_entry.amount = 0;
+    stake_entry.payer = ctx.accounts.payer.key();
 
     // assert metadata account derivation
     assert_derivation(
@@ -26,31 +26,31 @@ pub fn handler(ctx: Context<InitEntryCtx>, _user: Pubkey) -> Result<()> {
         ],
     )?;
     // check allowlist
-    //if !stake_pool.requires_collections.is_empty() || stake_pool.requires_authorization {
-        if stake_pool.requires_authorization {
+    if !stake_pool.requires_collections.is_empty() || stake_pool.requires_authorization {
         let mut allowed = false;
 
         if !ctx.accounts.original_mint_metadata.data_is_empty() {
-            let mint_metadata_data = ctx.accounts.original_mint_metadata.try_borrow_mut_data().expect("Failed to borrow data");
-            if ctx.accounts.original_mint_metadata.to_account_info().owner.key() != mpl_token_metadata::id() {
-                return Err(error!(ErrorCode::InvalidMintMetadataOwner));
-            }
-            let original_mint_metadata = Metadata::deserialize(&mut mint_metadata_data.as_ref()).expect("Failed to deserialize metadata");
-            if original_mint_metadata.mint != ctx.accounts.original_mint.key() {
-                return Err(error!(ErrorCode::InvalidMintMetadata));
+            let original_mint_metadata = load_original_mint_metadata(&ctx.accounts.original_mint_metadata, &ctx.accounts.original_mint.key())?;
+            if let Some(collection) = original_mint_metadata.collection {
+                if collection.verified && stake_pool.requires_collections.contains(&collection.key) {
+                    allowed = true
+                }
             }
+        }
 
-            // if !stake_pool.requires_collections.is_empty() && original_mint_metadata.collection.is_some() {
-            //     let collection = original_mint_metadata.collection.unwrap();
-            //     if collection.verified && stake_pool.requires_collections.contains(&collection.key) {
-            //         allowed = true
-            //     }
-            // }
+        if stake_pool.requires_authorization && !allowed {
+            if let (Some(root), Some(proof)) = (stake_pool.authorization_merkle_root, authorization_proof.as_ref()) {
+                allowed = verify_merkle_proof(proof, root, &stake_entry.original_mint);
+            }
         }
 
         if stake_pool.requires_authorization && !allowed {
+            // a failed proof without a per-mint authorization record is rejected like any other mint
             let remaining_accs = &mut ctx.remaining_accounts.iter();
-            let stake_entry_authorization_info = next_account_info(remaining_accs)?;
+            let stake_entry_authorization_info = match next_account_info(remaining_accs) {
+                Ok(info) => info,
+                Err(_) => return Err(error!(ErrorCode::MintNotAllowedInPool)),
+            };
             let stake_entry_authorization_account = match Account::<StakeAuthorizationRecord>::try_from(stake_entry_authorization_info) {
                 Ok(record) => record,
                 Err(_) => return Err(error!(ErrorCode::InvalidStakeAuthorizationRecord)),
@@ -7,19 +7,41 @@ use {
 
 pub fn handler(ctx: Context<InitPoolCtx>, ix: InitPoolIx) -> Result<()> {
     // ensure only authorized pubkey
-    require_keys_eq!(ix.authority, CHIEF_BREADHEAD);
+    if !ctx.accounts.program_config.admins.contains(&ix.authority) {
+        return Err(error!(ErrorCode::InvalidAuthority));
+    }
+
+    validate_rest_levels(&ix.rest_levels)?;
+    validate_progress_mode(ix.progress_mode, ix.progress_decay_levels)?;
+    validate_emergency_penalty(ix.emergency_forfeit_bps)?;
+    validate_stake_mode(ix.stake_mode)?;
+    if ix.requires_collections.len() > MAX_REQUIRED_COLLECTIONS {
+        return Err(error!(ErrorCode::TooManyRequiredCollections));
+    }
 
     let stake_pool = &mut ctx.accounts.stake_pool;
     stake_pool.bump = *ctx.bumps.get("stake_pool").unwrap();
-    // stake_pool.requires_collections = ix.requires_collections;
+    stake_pool.requires_collections = ix.requires_collections;
     stake_pool.requires_authorization = ix.requires_authorization;
+    stake_pool.authorization_merkle_root = ix.authorization_merkle_root;
     stake_pool.authority = ix.authority;
     stake_pool.reset_on_stake = ix.reset_on_stake;
     stake_pool.cooldown_seconds = ix.cooldown_seconds;
     stake_pool.min_stake_seconds = ix.min_stake_seconds;
     stake_pool.end_date = ix.end_date;
+    stake_pool.rest_levels = ix.rest_levels;
+    stake_pool.progress_mode = ix.progress_mode;
+    stake_pool.progress_decay_levels = ix.progress_decay_levels;
+    stake_pool.emergency_forfeit_bps = ix.emergency_forfeit_bps;
+    stake_pool.emergency_level_drop = ix.emergency_level_drop;
+    stake_pool.stake_mode = ix.stake_mode;
+    stake_pool.achievement_tier_count = 0;
     stake_pool.total_staked = 0;
 
+    let pool_stats = &mut ctx.accounts.pool_stats;
+    pool_stats.bump = *ctx.bumps.get("pool_stats").unwrap();
+    pool_stats.pool = stake_pool.key();
+
     Ok(())
 }
 
@@ -34,10 +56,20 @@ pub struct InitPoolCtx<'info> {
         bump
     )]
     pub stake_pool: Account<'info, StakePool>,
+    #[account(
+        init,
+        payer = authority,
+        space = POOL_STATS_SIZE,
+        seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()],
+        bump
+    )]
+    pub pool_stats: Account<'info, PoolStats>,
     pub original_mint: Account<'info, Mint>,
+    #[account(seeds = [PROGRAM_CONFIG_SEED.as_bytes()], bump = program_config.bump)]
+    pub program_config: Account<'info, ProgramConfig>,
     #[account(
         mut,
-        constraint = authority.key() == CHIEF_BREADHEAD
+        constraint = program_config.admins.contains(&authority.key())
         @ ErrorCode::InvalidAuthority
     )]
     pub authority: Signer<'info>,
@@ -48,9 +80,16 @@ pub struct InitPoolCtx<'info> {
 pub struct InitPoolIx {
     requires_collections: Vec<Pubkey>,
     requires_authorization: bool,
+    authorization_merkle_root: Option<[u8; 32]>,
     authority: Pubkey,
     reset_on_stake: bool,
     cooldown_seconds: Option<u32>,
     min_stake_seconds: Option<u32>,
     end_date: Option<i64>,
+    rest_levels: Vec<i64>,
+    progress_mode: u8,
+    progress_decay_levels: u8,
+    emergency_forfeit_bps: u16,
+    emergency_level_drop: u8,
+    stake_mode: u8,
 }
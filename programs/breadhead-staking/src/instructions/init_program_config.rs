@@ -0,0 +1,43 @@
+use {
+    crate::{errors::ErrorCode, program::BreadheadStaking, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<InitProgramConfigCtx>, admins: Vec<Pubkey>) -> Result<()> {
+    if admins.is_empty() || admins.len() > MAX_ADMINS {
+        return Err(error!(ErrorCode::InvalidAdmins));
+    }
+
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.bump = *ctx.bumps.get("program_config").unwrap();
+    program_config.admins = admins;
+    program_config.pending_admin = None;
+    program_config.pending_admin_proposer = None;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitProgramConfigCtx<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PROGRAM_CONFIG_SIZE,
+        seeds = [PROGRAM_CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    // only the upgrade authority can create the program config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, BreadheadStaking>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,72 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, SetAuthority, set_authority},
+    spl_token::instruction::AuthorityType,
+};
+
+pub fn handler(ctx: Context<InitRewardDistributorCtx>, ix: InitRewardDistributorIx) -> Result<()> {
+    let reward_distributor = &mut ctx.accounts.reward_distributor;
+    reward_distributor.bump = *ctx.bumps.get("reward_distributor").unwrap();
+    reward_distributor.stake_pool = ctx.accounts.stake_pool.key();
+    reward_distributor.kind = ix.kind;
+    reward_distributor.authority = ctx.accounts.authority.key();
+    reward_distributor.reward_mint = ctx.accounts.reward_mint.key();
+    reward_distributor.reward_rate = ix.reward_rate;
+    reward_distributor.max_supply = ix.max_supply;
+    reward_distributor.rewards_issued = 0;
+
+    match ix.kind {
+        k if k == RewardDistributorKind::Mint as u8 => {
+            // hand mint authority over to the distributor so it can mint rewards
+            set_authority(ctx.accounts.set_authority_ctx(), AuthorityType::MintTokens, Some(ctx.accounts.reward_distributor.key()))?;
+        }
+        k if k == RewardDistributorKind::Treasury as u8 => {}
+        _ => return Err(error!(ErrorCode::InvalidRewardDistributorKind)),
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitRewardDistributorCtx<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = REWARD_DISTRIBUTOR_SIZE,
+        seeds = [REWARD_DISTRIBUTOR_PREFIX.as_bytes(), stake_pool.key().as_ref()],
+        bump
+    )]
+    pub reward_distributor: Box<Account<'info, RewardDistributor>>,
+    #[account(
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut)]
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitRewardDistributorCtx <'info> {
+    pub fn set_authority_ctx(&self) -> CpiContext<'_,'_,'_, 'info, SetAuthority<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = SetAuthority {
+            account_or_mint: self.reward_mint.to_account_info(),
+            current_authority: self.authority.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitRewardDistributorIx {
+    kind: u8,
+    reward_rate: u64,
+    max_supply: Option<u64>,
+}
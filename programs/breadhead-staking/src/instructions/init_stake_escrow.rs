@@ -0,0 +1,48 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token, TokenAccount},
+};
+
+pub fn handler(_ctx: Context<InitStakeEscrowCtx>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitStakeEscrowCtx<'info> {
+    #[account(
+        constraint = stake_pool.stake_mode == StakeMode::Escrow as u8
+        @ ErrorCode::InvalidStakeMode
+    )]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    #[account(constraint = original_mint.key() == stake_entry.original_mint @ ErrorCode::InvalidOriginalMint)]
+    pub original_mint: Box<Account<'info, Mint>>,
+
+    // holds the token while it is staked
+    #[account(
+        init,
+        payer = payer,
+        seeds = [STAKE_ESCROW_PREFIX.as_bytes(), stake_entry.key().as_ref()],
+        bump,
+        token::mint = original_mint,
+        token::authority = program_authority
+    )]
+    pub escrow_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -0,0 +1,137 @@
+use mpl_token_metadata::utils::assert_derivation;
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token},
+    mpl_token_metadata::{
+        instruction::create_metadata_accounts_v3,
+        state::Metadata,
+        ID as metadata_program_id,
+    },
+    solana_program::program::invoke_signed
+};
+
+pub fn handler(ctx: Context<InitStakeMintCtx>) -> Result<()> {
+    // assert metadata account derivation
+    assert_derivation(
+        &mpl_token_metadata::id(),
+        &ctx.accounts.original_mint_metadata.to_account_info(),
+        &[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            ctx.accounts.original_mint.key().as_ref(),
+        ],
+    )?;
+    if ctx.accounts.original_mint_metadata.owner.key() != mpl_token_metadata::id() {
+        return Err(error!(ErrorCode::InvalidMintMetadataOwner));
+    }
+    let original_mint_metadata = {
+        let mint_metadata_data = ctx.accounts.original_mint_metadata.try_borrow_data()?;
+        Metadata::deserialize(&mut mint_metadata_data.as_ref()).map_err(|_| error!(ErrorCode::InvalidMintMetadata))?
+    };
+    if original_mint_metadata.mint != ctx.accounts.original_mint.key() {
+        return Err(error!(ErrorCode::InvalidMintMetadata));
+    }
+
+    let auth_bump = *ctx.bumps.get("program_authority").unwrap();
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+
+    // receipt metadata mirrors the original breadhead
+    let metadata_ix = create_metadata_accounts_v3(
+        ctx.accounts.metadata_program.key(),
+        ctx.accounts.stake_mint_metadata.key(),
+        ctx.accounts.stake_mint.key(),
+        ctx.accounts.program_authority.key(),
+        ctx.accounts.payer.key(),
+        ctx.accounts.program_authority.key(),
+        original_mint_metadata.data.name.trim_matches(char::from(0)).to_string(),
+        original_mint_metadata.data.symbol.trim_matches(char::from(0)).to_string(),
+        original_mint_metadata.data.uri.trim_matches(char::from(0)).to_string(),
+        None,
+        0,
+        true,
+        true,
+        None,
+        None,
+        None
+    );
+    invoke_signed(
+        &metadata_ix,
+        &[
+            ctx.accounts.metadata_program.to_account_info(),
+            ctx.accounts.stake_mint_metadata.to_account_info(),
+            ctx.accounts.stake_mint.to_account_info(),
+            ctx.accounts.program_authority.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.rent.to_account_info()
+        ],
+        signer
+    )?;
+
+    let stake_entry = &mut ctx.accounts.stake_entry;
+    stake_entry.stake_mint = Some(ctx.accounts.stake_mint.key());
+    stake_entry.stake_mint_claimed = false;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitStakeMintCtx<'info> {
+    #[account(
+        mut,
+        constraint = stake_entry.stake_mint.is_none() @ ErrorCode::StakeMintAlreadyInitialized,
+        constraint = stake_entry.amount == 0 @ ErrorCode::StakeEntryAlreadyStaked
+    )]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    #[account(constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(constraint = original_mint.key() == stake_entry.original_mint @ ErrorCode::InvalidOriginalMint)]
+    pub original_mint: Box<Account<'info, Mint>>,
+    /// CHECK: derivation and owner are checked in the handler
+    pub original_mint_metadata: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [STAKE_MINT_PREFIX.as_bytes(), stake_entry.key().as_ref()],
+        bump,
+        mint::decimals = original_mint.decimals,
+        mint::authority = program_authority,
+        mint::freeze_authority = program_authority
+    )]
+    pub stake_mint: Box<Account<'info, Mint>>,
+    /// CHECK: metadata program verifies the derivation when creating the metadata
+    #[account(mut)]
+    pub stake_mint_metadata: AccountInfo<'info>,
+
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        mut,
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    // only the pool authority or the last staker can attach a receipt mint,
+    // whoever paid for the entry does not own it
+    #[account(
+        mut,
+        constraint = payer.key() == stake_pool.authority
+        || payer.key() == stake_entry.last_staker
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub payer: Signer<'info>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+    /// CHECK: constraint verifies this is the metadata program
+    #[account(
+        constraint = metadata_program.key() == metadata_program_id
+        @ ErrorCode::InvalidMetadataProgram
+    )]
+    pub metadata_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
@@ -1,11 +1,60 @@
+// every instruction module exports its own handler, lib.rs calls them by path
+#![allow(ambiguous_glob_reexports)]
+
 pub mod init_entry;
 pub mod init_pool;
 pub mod stake;
 pub mod unstake;
 pub mod calculate_reward;
+pub mod update_pool;
+pub mod close_stake_pool;
+pub mod close_stake_entry;
+pub mod reassign_stake_entry;
+pub mod init_program_config;
+pub mod propose_admin;
+pub mod accept_admin;
+pub mod remove_admin;
+pub mod init_achievement_tier;
+pub mod update_achievement_tier;
+pub mod claim_badge;
+pub mod init_reward_distributor;
+pub mod claim_rewards;
+pub mod authorize_mint;
+pub mod deauthorize_mint;
+pub mod stake_many;
+pub mod unstake_many;
+pub mod refresh_resting_level;
+pub mod emergency_unstake;
+pub mod init_stake_mint;
+pub mod init_stake_escrow;
+pub mod settle_entry;
+pub mod close_reward_distributor;
 
 pub use init_entry::*;
 pub use init_pool::*;
 pub use stake::*;
 pub use unstake::*;
-pub use calculate_reward::*;
\ No newline at end of file
+pub use calculate_reward::*;
+pub use update_pool::*;
+pub use close_stake_pool::*;
+pub use close_stake_entry::*;
+pub use reassign_stake_entry::*;
+pub use init_program_config::*;
+pub use propose_admin::*;
+pub use accept_admin::*;
+pub use remove_admin::*;
+pub use init_achievement_tier::*;
+pub use update_achievement_tier::*;
+pub use claim_badge::*;
+pub use init_reward_distributor::*;
+pub use claim_rewards::*;
+pub use authorize_mint::*;
+pub use deauthorize_mint::*;
+pub use stake_many::*;
+pub use unstake_many::*;
+pub use refresh_resting_level::*;
+pub use emergency_unstake::*;
+pub use init_stake_mint::*;
+pub use init_stake_escrow::*;
+pub use settle_entry::*;
+pub use close_reward_distributor::*;
\ No newline at end of file
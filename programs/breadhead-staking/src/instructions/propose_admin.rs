@@ -0,0 +1,28 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<ProposeAdminCtx>, new_admin: Pubkey) -> Result<()> {
+    // new admin must accept before taking over the proposing admin's seat
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.pending_admin = Some(new_admin);
+    program_config.pending_admin_proposer = Some(ctx.accounts.admin.key());
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdminCtx<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED.as_bytes()],
+        bump = program_config.bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        constraint = program_config.admins.contains(&admin.key())
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub admin: Signer<'info>,
+}
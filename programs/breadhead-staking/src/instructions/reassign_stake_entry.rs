@@ -0,0 +1,108 @@
+use {
+    crate::{errors::ErrorCode, state::*, utils::close_escrow},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{Mint, Token},
+};
+
+pub fn handler(ctx: Context<ReassignStakeEntryCtx>, _ix: ReassignStakeEntryIx) -> Result<()> {
+    let stake_entry = &ctx.accounts.stake_entry;
+    let new_stake_entry = &mut ctx.accounts.new_stake_entry;
+
+    // stake entry seeds include the pool, so the entry is copied to its new address and the old one is closed
+    new_stake_entry.bump = *ctx.bumps.get("new_stake_entry").unwrap();
+    new_stake_entry.pool = ctx.accounts.new_stake_pool.key();
+    new_stake_entry.amount = 0;
+    new_stake_entry.original_mint = stake_entry.original_mint;
+    new_stake_entry.original_mint_claimed = stake_entry.original_mint_claimed;
+    new_stake_entry.last_staker = stake_entry.last_staker;
+    new_stake_entry.last_staked_at = stake_entry.last_staked_at;
+    new_stake_entry.total_stake_seconds = stake_entry.total_stake_seconds;
+    // carried seconds that were already paid out are not paid again by the new pool
+    new_stake_entry.reward_seconds_claimed = stake_entry.reward_seconds_claimed;
+    new_stake_entry.stake_mint_claimed = stake_entry.stake_mint_claimed;
+    new_stake_entry.kind = stake_entry.kind;
+    new_stake_entry.stake_mint = stake_entry.stake_mint;
+    new_stake_entry.cooldown_start_seconds = None;
+    new_stake_entry.payer = ctx.accounts.authority.key();
+
+    // the escrow is seeded by the old entry, the new entry creates its own
+    if !ctx.accounts.stake_escrow.data_is_empty() {
+        close_escrow(
+            &ctx.accounts.stake_escrow,
+            &ctx.accounts.payer,
+            &ctx.accounts.program_authority,
+            &ctx.accounts.token_program.to_account_info(),
+            *ctx.bumps.get("program_authority").unwrap(),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(ix: ReassignStakeEntryIx)]
+pub struct ReassignStakeEntryCtx<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [STAKE_ENTRY_PREFIX.as_bytes(), stake_entry.pool.as_ref(), stake_entry.original_mint.as_ref(), get_stake_seed(original_mint.supply, ix.user).as_ref()],
+        bump = stake_entry.bump,
+        constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool,
+        constraint = stake_entry.amount == 0 @ ErrorCode::StakeEntryAlreadyStaked,
+        // the receipt mint is seeded by the old entry address
+        constraint = stake_entry.stake_mint.is_none() @ ErrorCode::CannotReassignStakeEntry
+    )]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    #[account(
+        init,
+        payer = authority,
+        space = STAKE_ENTRY_SIZE,
+        seeds = [STAKE_ENTRY_PREFIX.as_bytes(), new_stake_pool.key().as_ref(), original_mint.key().as_ref(), get_stake_seed(original_mint.supply, ix.user).as_ref()],
+        bump,
+    )]
+    pub new_stake_entry: Box<Account<'info, StakeEntry>>,
+
+    #[account(constraint = stake_pool.authority == authority.key() @ ErrorCode::InvalidPoolAuthority)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(constraint = new_stake_pool.authority == authority.key() @ ErrorCode::InvalidPoolAuthority)]
+    pub new_stake_pool: Box<Account<'info, StakePool>>,
+    #[account(constraint = original_mint.key() == stake_entry.original_mint @ ErrorCode::InvalidOriginalMint)]
+    pub original_mint: Box<Account<'info, Mint>>,
+    /// CHECK: closed in the handler if the old pool created it
+    #[account(
+        mut,
+        seeds = [STAKE_ESCROW_PREFIX.as_bytes(), stake_entry.key().as_ref()],
+        bump
+    )]
+    pub stake_escrow: AccountInfo<'info>,
+    /// CHECK: progress kept by the old pool would be left behind, so it must not exist
+    #[account(
+        seeds = [stake_entry.last_staker.as_ref(), stake_pool.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
+        bump,
+        constraint = stake_state.data_is_empty() @ ErrorCode::CannotReassignStakeEntry
+    )]
+    pub stake_state: AccountInfo<'info>,
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: constraint verifies this is the account that paid for the stake entry
+    #[account(
+        mut,
+        constraint = payer.key() == stake_entry.payer
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ReassignStakeEntryIx {
+    user: Pubkey,
+}
@@ -0,0 +1,59 @@
+use {
+    crate::{errors::ErrorCode, events::RestingLevelChanged, instructions::calculate_reward::refresh_levels, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<RefreshRestingLevelCtx>) -> Result<()> {
+    let stake_state = &mut ctx.accounts.stake_state;
+    let old_resting_level = refresh_levels(&ctx.accounts.stake_pool, stake_state, ctx.remaining_accounts)?;
+    ctx.accounts.staker_profile.record_levels(stake_state.resting_level, stake_state.achievment_level);
+
+    if old_resting_level != stake_state.resting_level {
+        ctx.accounts.pool_stats.record_level_change(old_resting_level, stake_state.resting_level);
+        let stake_entry = &ctx.accounts.stake_entry;
+        let now = Clock::get().unwrap().unix_timestamp;
+        emit!(RestingLevelChanged {
+            pool: ctx.accounts.stake_pool.key(),
+            mint: stake_state.original_mint,
+            user: stake_entry.last_staker,
+            stake_start: stake_state.stake_start,
+            amount: stake_entry.amount,
+            total_stake_seconds: stake_entry.total_stake_seconds.saturating_add(stake_entry.pending_stake_seconds(now, ctx.accounts.stake_pool.end_date)),
+            old_resting_level,
+            new_resting_level: stake_state.resting_level,
+            achievment_level: stake_state.achievment_level,
+            timestamp: now,
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefreshRestingLevelCtx<'info> {
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
+    #[account(
+        constraint = stake_entry.pool == stake_pool.key()
+        && stake_entry.original_mint == stake_state.original_mint
+        && stake_entry.amount > 0
+        @ ErrorCode::InvalidStakeEntry
+    )]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    #[account(
+        mut,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), stake_entry.last_staker.as_ref()],
+        bump = staker_profile.bump
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
+    // anyone can crank, the state is derived from the staker recorded on the entry
+    #[account(
+        mut,
+        seeds = [stake_entry.last_staker.as_ref(), stake_pool.key().as_ref(), stake_state.original_mint.as_ref(), STAKE_STATE_SEED.as_bytes()],
+        bump = stake_state.bump,
+        constraint = stake_state.pool == stake_pool.key()
+        @ ErrorCode::InvalidStakePool
+    )]
+    pub stake_state: Account<'info, StakeState>,
+}
@@ -0,0 +1,36 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<RemoveAdminCtx>) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    let admin = ctx.accounts.admin.key();
+
+    if program_config.admins.len() == 1 {
+        return Err(error!(ErrorCode::CannotRemoveLastAdmin));
+    }
+    // admins can only step down themselves, one admin cannot remove the others
+    program_config.admins.retain(|key| key != &admin);
+    if program_config.pending_admin_proposer == Some(admin) {
+        program_config.pending_admin = None;
+        program_config.pending_admin_proposer = None;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveAdminCtx<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED.as_bytes()],
+        bump = program_config.bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        constraint = program_config.admins.contains(&admin.key())
+        @ ErrorCode::InvalidAuthority
+    )]
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,55 @@
+use {
+    crate::{errors::ErrorCode, events::EntrySettled, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<SettleEntryCtx>) -> Result<()> {
+    let end_date = ctx.accounts.stake_pool.end_date.unwrap();
+
+    // finalize the seconds accrued up to the end date
+    let stake_entry = &mut ctx.accounts.stake_entry;
+    let stake_seconds = stake_entry.pending_stake_seconds(end_date, Some(end_date));
+    stake_entry.total_stake_seconds = stake_entry.total_stake_seconds.saturating_add(stake_seconds);
+    // nothing accrues past the end date so settling again is a no-op
+    stake_entry.last_staked_at = stake_entry.last_staked_at.max(end_date);
+
+    ctx.accounts.pool_stats.record_stake_seconds(ctx.accounts.stake_pool.total_staked, stake_seconds);
+    ctx.accounts.staker_profile.record_stake_seconds(stake_seconds);
+
+    emit!(EntrySettled {
+        pool: ctx.accounts.stake_pool.key(),
+        mint: stake_entry.original_mint,
+        user: stake_entry.last_staker,
+        settled_stake_seconds: stake_seconds,
+        total_stake_seconds: stake_entry.total_stake_seconds,
+        end_date,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleEntryCtx<'info> {
+    #[account(
+        constraint = stake_pool.end_date.is_some()
+        && Clock::get().unwrap().unix_timestamp > stake_pool.end_date.unwrap()
+        @ ErrorCode::StakePoolHasNotEnded
+    )]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
+    // anyone can settle, the profile is derived from the staker recorded on the entry
+    #[account(
+        mut,
+        constraint = stake_entry.pool == stake_pool.key()
+        && stake_entry.amount > 0
+        @ ErrorCode::InvalidStakeEntry
+    )]
+    pub stake_entry: Box<Account<'info, StakeEntry>>,
+    #[account(
+        mut,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), stake_entry.last_staker.as_ref()],
+        bump = staker_profile.bump
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
+}
@@ -1,85 +1,146 @@
 use {
-    crate::{errors::ErrorCode, state::*},
+    crate::{errors::ErrorCode, events::Staked, state::*, utils::{freeze_token_account, is_programmable, load_original_mint_metadata, lock_programmable, mint_receipt, validate_escrow_token_account, ProgrammableAccounts}},
     anchor_lang::prelude::*,
-    anchor_spl::token::{Mint, Token, TokenAccount, Approve, approve},
+    anchor_spl::token::{Mint, Token, TokenAccount, Approve, approve, Transfer, transfer},
     mpl_token_metadata::{
-        instruction::freeze_delegated_account,
         ID as metadata_program_id,
         utils::is_master_edition
     },
-    anchor_safe_math::{SafeMath},
-    solana_program::program::invoke_signed
+    solana_program::sysvar::instructions::ID as sysvar_instructions_id,
 };
 
-pub fn handler(ctx: Context<StakeCtx>, amount: u64) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, StakeCtx<'info>>, amount: u64) -> Result<()> {
 
     if ctx.accounts.stake_pool.end_date.is_some() && Clock::get().unwrap().unix_timestamp > ctx.accounts.stake_pool.end_date.unwrap() {
         return Err(error!(ErrorCode::StakePoolHasEnded));
     }
 
-    if ctx.accounts.stake_entry.amount != 0 {
-        ctx.accounts.stake_entry.total_stake_seconds = ctx.accounts.stake_entry.total_stake_seconds.saturating_add(
-            (u128::try_from(ctx.accounts.stake_entry.cooldown_start_seconds.unwrap_or(Clock::get().unwrap().unix_timestamp))
-                .unwrap()
-                .saturating_sub(u128::try_from(ctx.accounts.stake_entry.last_staked_at).unwrap()))
-            .checked_mul(u128::try_from(ctx.accounts.stake_entry.amount).unwrap())
-            .unwrap(),
-        );
-        ctx.accounts.stake_entry.cooldown_start_seconds = None;
+    if amount == 0 || amount > ctx.accounts.user_original_mint_token_account.amount {
+        return Err(error!(ErrorCode::InvalidStakeAmount));
     }
+    // fungible and semi-fungible entries are keyed per user and can only be staked into escrow
+    let fungible = ctx.accounts.original_mint.supply > 1;
 
-    // approve program authority over token account
-    approve(ctx.accounts.approve_ctx(), 1)?;
-
-    let stake_pool = &mut ctx.accounts.stake_pool;
-    let stake_entry = &mut ctx.accounts.stake_entry;
-
-    // freeze token account
-    let freeze_ix = freeze_delegated_account(
-        ctx.accounts.metadata_program.key(),
-        ctx.accounts.program_authority.key(),
-        ctx.accounts.user_original_mint_token_account.key(),
-        ctx.accounts.master_edition.key(),
-        ctx.accounts.original_mint.key()
-    );
-
-    let auth_bump = *ctx.bumps.get("program_authority").unwrap();
-    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
-    let signer = &[&auth_seeds[..]];
-    invoke_signed(
-        &freeze_ix,
-        &[
-            ctx.accounts.metadata_program.to_account_info(),
-            ctx.accounts.program_authority.to_account_info(),
-            ctx.accounts.user_original_mint_token_account.to_account_info(),
-            ctx.accounts.master_edition.to_account_info(),
-            ctx.accounts.original_mint.to_account_info()
-        ],
-        signer
+    let staked_token_account = if ctx.accounts.stake_pool.stake_mode == StakeMode::Escrow as u8 {
+        // programmable nfts can only be transferred through the metadata program
+        if !fungible && is_programmable(&load_original_mint_metadata(&ctx.accounts.original_mint_metadata, &ctx.accounts.original_mint.key())?) {
+            return Err(error!(ErrorCode::InvalidStakeMode));
+        }
+        // move the tokens into the stake entry escrow
+        validate_escrow_token_account(&ctx.accounts.escrow_token_account, &ctx.accounts.stake_entry.key(), &ctx.accounts.original_mint.key())?;
+        transfer(ctx.accounts.transfer_to_escrow_ctx(), amount)?;
+        ctx.accounts.escrow_token_account.key()
+    } else {
+        if fungible {
+            return Err(error!(ErrorCode::InvalidStakeMode));
+        }
+        let original_mint_metadata = load_original_mint_metadata(&ctx.accounts.original_mint_metadata, &ctx.accounts.original_mint.key())?;
+        if is_programmable(&original_mint_metadata) {
+            // delegate and lock through the metadata program, the token stays in the user's account
+            lock_programmable(&ctx.accounts.programmable_accounts(), &original_mint_metadata, *ctx.bumps.get("program_authority").unwrap())?;
+        } else {
+            // approve program authority over token account
+            approve(ctx.accounts.approve_ctx(), amount)?;
+
+            // freeze token account
+            freeze_token_account(
+                &ctx.accounts.metadata_program,
+                &ctx.accounts.program_authority,
+                &ctx.accounts.user_original_mint_token_account.to_account_info(),
+                &ctx.accounts.master_edition,
+                &ctx.accounts.original_mint.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                *ctx.bumps.get("program_authority").unwrap()
+            )?;
+        }
+        ctx.accounts.user_original_mint_token_account.key()
+    };
+
+    // topping up an entry that is already staked keeps its stake state and pool stats
+    let newly_staked = ctx.accounts.stake_entry.amount == 0;
+    let stake_seconds = record_stake(&mut ctx.accounts.stake_pool, &mut ctx.accounts.stake_entry, ctx.accounts.user.key(), amount);
+
+    // mint a receipt for the staked position
+    mint_receipt(
+        &mut ctx.accounts.stake_entry,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.key(),
+        &ctx.accounts.program_authority,
+        &ctx.accounts.token_program.to_account_info(),
+        *ctx.bumps.get("program_authority").unwrap(),
+        amount
     )?;
 
+    // update user stake state, resuming progress preserved from a previous stake
+    let stake_state = &mut ctx.accounts.stake_state;
+    stake_state.bump = *ctx.bumps.get("stake_state").unwrap();
+    stake_state.token_account = staked_token_account;
+    stake_state.original_mint = ctx.accounts.original_mint.key();
+    stake_state.pool = ctx.accounts.stake_pool.key();
+    if newly_staked {
+        resume_stake_state(&ctx.accounts.stake_pool, stake_state, Clock::get().unwrap().unix_timestamp);
+    }
+
+    let staker_profile = &mut ctx.accounts.staker_profile;
+    staker_profile.bump = *ctx.bumps.get("staker_profile").unwrap();
+    staker_profile.pool = ctx.accounts.stake_pool.key();
+    staker_profile.user = ctx.accounts.user.key();
+    if staker_profile.first_staked_at == 0 {
+        staker_profile.first_staked_at = Clock::get().unwrap().unix_timestamp;
+    }
+    staker_profile.record_stake_seconds(stake_seconds);
+    if newly_staked {
+        ctx.accounts.pool_stats.record_stake(staker_profile, ctx.accounts.stake_pool.total_staked, stake_seconds, ctx.accounts.stake_state.resting_level);
+    } else {
+        ctx.accounts.pool_stats.record_stake_seconds(ctx.accounts.stake_pool.total_staked, stake_seconds);
+    }
+
+    emit!(Staked {
+        pool: ctx.accounts.stake_pool.key(),
+        mint: ctx.accounts.original_mint.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        staked_at: ctx.accounts.stake_entry.last_staked_at,
+        total_stake_seconds: ctx.accounts.stake_entry.total_stake_seconds,
+    });
+
+    Ok(())
+}
+
+// update stake entry and pool once the token is locked, returns the stake seconds credited to the entry
+pub fn record_stake(stake_pool: &mut StakePool, stake_entry: &mut StakeEntry, user: Pubkey, amount: u64) -> u128 {
+    let now = Clock::get().unwrap().unix_timestamp;
+    let mut stake_seconds = 0;
+
+    if stake_entry.amount != 0 {
+        stake_seconds = stake_entry.pending_stake_seconds(now, stake_pool.end_date);
+        stake_entry.total_stake_seconds = stake_entry.total_stake_seconds.saturating_add(stake_seconds);
+        stake_entry.cooldown_start_seconds = None;
+    }
 
     if stake_pool.reset_on_stake && stake_entry.amount == 0 {
         stake_entry.total_stake_seconds = 0;
+        stake_entry.reward_seconds_claimed = 0;
     }
 
-    // update stake entry
-    stake_entry.last_staked_at = Clock::get().unwrap().unix_timestamp;
-    stake_entry.last_staker = ctx.accounts.user.key();
-    stake_entry.amount = stake_entry.amount.checked_add(1).unwrap();
+    stake_entry.last_staked_at = now;
+    stake_entry.last_staker = user;
+    stake_entry.amount = stake_entry.amount.checked_add(amount).unwrap();
 
-    stake_pool.total_staked = stake_pool.total_staked.checked_add(1).unwrap();
+    stake_pool.total_staked = stake_pool.total_staked.checked_add(amount).unwrap();
 
-    // update user stake state
-    ctx.accounts.stake_state.bump = *ctx.bumps.get("stake_state").unwrap();
-    ctx.accounts.stake_state.stake_start = Clock::get().unwrap().unix_timestamp;
-    ctx.accounts.stake_state.token_account = ctx.accounts.user_original_mint_token_account.key();
-    ctx.accounts.stake_state.original_mint = ctx.accounts.original_mint.key();
-    ctx.accounts.stake_state.pool = stake_pool.key();
-    ctx.accounts.stake_state.resting_level = 0;
-    ctx.accounts.stake_state.achievment_level = Achievement::DoughBoy;
+    stake_seconds
+}
 
-    Ok(())
+// start the stake clock, backdated to any resting level kept by the pool progress mode
+pub fn resume_stake_state(stake_pool: &StakePool, stake_state: &mut StakeState, now: i64) {
+    if stake_pool.progress_mode == ProgressMode::Reset as u8 {
+        stake_state.resting_level = 0;
+        stake_state.achievment_level = None;
+    }
+    // rest levels may have been shortened since the progress was recorded
+    stake_state.resting_level = stake_state.resting_level.min(stake_pool.rest_levels.len() as u8);
+    stake_state.stake_start = resume_stake_start(now, stake_state.resting_level, &stake_pool.rest_levels);
 }
 
 #[derive(Accounts)]
@@ -89,6 +150,8 @@ pub struct StakeCtx<'info> {
 
     #[account(mut, constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
     pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
 
     /// CHECK: Safe this is used a program signer
     #[account(
@@ -98,17 +161,35 @@ pub struct StakeCtx<'info> {
     )]
     pub program_authority: AccountInfo<'info>,
     pub original_mint: Box<Account<'info, Mint>>,
-    /// CHECK: constraint verifies this is a master edition
+    /// CHECK: constraint verifies this is a master edition, fungible mints have none
     #[account(constraint = 
-        is_master_edition(
-            &master_edition, original_mint.decimals, original_mint.supply) == true
+        original_mint.supply > 1 || is_master_edition(
+            &master_edition, original_mint.decimals, original_mint.supply)
             @ ErrorCode::InvalidMasterEdition
         )]
         pub master_edition: AccountInfo<'info>,
+    /// CHECK: derivation and owner verified in the handler
+    #[account(mut)]
+    pub original_mint_metadata: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts, verified against the token account in the handler
+    #[account(mut)]
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts with a rule set, verified against the metadata in the handler
+    pub authorization_rules: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts with a rule set, verified by the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
 
     // user
     #[account(mut)]
     pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = STAKER_PROFILE_SIZE
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
     #[account(
         mut,
         constraint = user_original_mint_token_account.amount > 0
@@ -117,10 +198,13 @@ pub struct StakeCtx<'info> {
         @ ErrorCode::InvalidUserOriginalMintTokenAccount
     )]
     pub user_original_mint_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: only used in escrow mode, verified against the stake entry in the handler
+    #[account(mut)]
+    pub escrow_token_account: AccountInfo<'info>,
 
     #[account(
-        init,
-        seeds = [user.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
+        init_if_needed,
+        seeds = [user.key().as_ref(), stake_pool.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
         bump,
         payer = user,
         space = STAKE_STATE_SIZE
@@ -135,7 +219,13 @@ pub struct StakeCtx<'info> {
         @ ErrorCode::InvalidMetadataProgram
     )]
     pub metadata_program: AccountInfo<'info>,
-    pub system_program: Program<'info, System>
+    pub system_program: Program<'info, System>,
+    /// CHECK: constraint verifies this is the instructions sysvar
+    #[account(
+        constraint = sysvar_instructions.key() == sysvar_instructions_id
+        @ ErrorCode::InvalidInstructionsSysvar
+    )]
+    pub sysvar_instructions: AccountInfo<'info>
 }
 
 impl<'info> StakeCtx <'info> {
@@ -149,4 +239,33 @@ impl<'info> StakeCtx <'info> {
 
         CpiContext::new(cpi_program, cpi_accounts)
     }
+
+    pub fn transfer_to_escrow_ctx(&self) -> CpiContext<'_,'_,'_, 'info, Transfer<'info>> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.user_original_mint_token_account.to_account_info(),
+            to: self.escrow_token_account.to_account_info(),
+            authority: self.user.to_account_info()
+        };
+
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn programmable_accounts(&self) -> ProgrammableAccounts<'info> {
+        ProgrammableAccounts {
+            metadata_program: self.metadata_program.to_account_info(),
+            metadata: self.original_mint_metadata.to_account_info(),
+            master_edition: self.master_edition.to_account_info(),
+            token_record: self.token_record.to_account_info(),
+            mint: self.original_mint.to_account_info(),
+            token_account: self.user_original_mint_token_account.to_account_info(),
+            user: self.user.to_account_info(),
+            program_authority: self.program_authority.to_account_info(),
+            authorization_rules_program: self.authorization_rules_program.to_account_info(),
+            authorization_rules: self.authorization_rules.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            sysvar_instructions: self.sysvar_instructions.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,248 @@
+use {
+    crate::{errors::ErrorCode, events::Staked, instructions::stake::{record_stake, resume_stake_state}, state::*, utils::freeze_token_account},
+    anchor_lang::prelude::*,
+    anchor_lang::AccountsExit,
+    anchor_spl::token::{Mint, Token, TokenAccount, Approve, approve},
+    mpl_token_metadata::{
+        ID as metadata_program_id,
+        utils::is_master_edition
+    },
+    solana_program::{program::{invoke, invoke_signed}, system_instruction}
+};
+
+// remaining accounts are passed in groups of (stake_entry, original_mint, master_edition, token_account, stake_state)
+pub const STAKE_MANY_GROUP_SIZE: usize = 5;
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, StakeManyCtx<'info>>) -> Result<()> {
+
+    if ctx.accounts.stake_pool.end_date.is_some() && Clock::get().unwrap().unix_timestamp > ctx.accounts.stake_pool.end_date.unwrap() {
+        return Err(error!(ErrorCode::StakePoolHasEnded));
+    }
+
+    // batches only support freeze mode
+    if ctx.accounts.stake_pool.stake_mode != StakeMode::Freeze as u8 {
+        return Err(error!(ErrorCode::InvalidStakeMode));
+    }
+
+    if ctx.remaining_accounts.is_empty() || !ctx.remaining_accounts.chunks_exact(STAKE_MANY_GROUP_SIZE).remainder().is_empty() {
+        return Err(error!(ErrorCode::InvalidRemainingAccounts));
+    }
+
+    let auth_bump = *ctx.bumps.get("program_authority").unwrap();
+    let user = ctx.accounts.user.key();
+
+    let staker_profile = &mut ctx.accounts.staker_profile;
+    staker_profile.bump = *ctx.bumps.get("staker_profile").unwrap();
+    staker_profile.pool = ctx.accounts.stake_pool.key();
+    staker_profile.user = user;
+    if staker_profile.first_staked_at == 0 {
+        staker_profile.first_staked_at = Clock::get().unwrap().unix_timestamp;
+    }
+
+    for group in ctx.remaining_accounts.chunks(STAKE_MANY_GROUP_SIZE) {
+        let stake_entry_info = &group[0];
+        let original_mint_info = &group[1];
+        let master_edition_info = &group[2];
+        let token_account_info = &group[3];
+        let stake_state_info = &group[4];
+
+        let mut stake_entry = Account::<StakeEntry>::try_from(stake_entry_info)?;
+        let original_mint = Account::<Mint>::try_from(original_mint_info)?;
+        let token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+
+        // same checks StakeCtx applies to a single stake
+        let stake_entry_address = Pubkey::create_program_address(
+            &[
+                STAKE_ENTRY_PREFIX.as_bytes(),
+                stake_entry.pool.as_ref(),
+                stake_entry.original_mint.as_ref(),
+                get_stake_seed(original_mint.supply, user).as_ref(),
+                &[stake_entry.bump],
+            ],
+            ctx.program_id,
+        ).map_err(|_| error!(ErrorCode::InvalidStakeEntry))?;
+        if stake_entry_address != stake_entry.key() {
+            return Err(error!(ErrorCode::InvalidStakeEntry));
+        }
+        if stake_entry.pool != ctx.accounts.stake_pool.key() {
+            return Err(error!(ErrorCode::InvalidStakePool));
+        }
+        // receipts are not minted in batches
+        if stake_entry.stake_mint.is_some() {
+            return Err(error!(ErrorCode::InvalidReceiptMint));
+        }
+        if original_mint.key() != stake_entry.original_mint {
+            return Err(error!(ErrorCode::InvalidOriginalMint));
+        }
+        if !is_master_edition(master_edition_info, original_mint.decimals, original_mint.supply) {
+            return Err(error!(ErrorCode::InvalidMasterEdition));
+        }
+        if token_account.amount == 0 || token_account.mint != stake_entry.original_mint || token_account.owner != user {
+            return Err(error!(ErrorCode::InvalidUserOriginalMintTokenAccount));
+        }
+
+        // approve program authority over token account
+        approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: token_account_info.clone(),
+                    delegate: ctx.accounts.program_authority.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info()
+                }
+            ),
+            1
+        )?;
+
+        // freeze token account
+        freeze_token_account(
+            &ctx.accounts.metadata_program,
+            &ctx.accounts.program_authority,
+            token_account_info,
+            master_edition_info,
+            original_mint_info,
+            &ctx.accounts.token_program.to_account_info(),
+            auth_bump
+        )?;
+
+        let stake_seconds = record_stake(&mut ctx.accounts.stake_pool, &mut stake_entry, user, 1);
+        stake_entry.exit(ctx.program_id)?;
+
+        let mut stake_state = init_or_load_stake_state(
+            ctx.program_id,
+            stake_state_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.stake_pool.key(),
+            &original_mint.key(),
+        )?;
+        stake_state.token_account = token_account.key();
+        resume_stake_state(&ctx.accounts.stake_pool, &mut stake_state, Clock::get().unwrap().unix_timestamp);
+        stake_state.exit(ctx.program_id)?;
+
+        ctx.accounts.pool_stats.record_stake(&mut ctx.accounts.staker_profile, ctx.accounts.stake_pool.total_staked, stake_seconds, stake_state.resting_level);
+        ctx.accounts.staker_profile.record_stake_seconds(stake_seconds);
+
+        emit!(Staked {
+            pool: ctx.accounts.stake_pool.key(),
+            mint: original_mint.key(),
+            user,
+            amount: 1,
+            staked_at: stake_entry.last_staked_at,
+            total_stake_seconds: stake_entry.total_stake_seconds,
+        });
+    }
+
+    Ok(())
+}
+
+// create the stake state PDA the way `init_if_needed` does for StakeCtx, or load the one preserved from a previous stake
+fn init_or_load_stake_state<'info>(
+    program_id: &Pubkey,
+    stake_state_info: &AccountInfo<'info>,
+    user: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    pool: &Pubkey,
+    original_mint: &Pubkey,
+) -> Result<Account<'info, StakeState>> {
+    let (stake_state_address, stake_state_bump) = Pubkey::find_program_address(
+        &[user.key().as_ref(), pool.as_ref(), original_mint.as_ref(), STAKE_STATE_SEED.as_bytes()],
+        program_id,
+    );
+    if stake_state_address != stake_state_info.key() {
+        return Err(error!(ErrorCode::InvalidStakeState));
+    }
+
+    if stake_state_info.data_is_empty() {
+        let user_key = user.key();
+        let stake_state_seeds: &[&[u8]] = &[user_key.as_ref(), pool.as_ref(), original_mint.as_ref(), STAKE_STATE_SEED.as_bytes(), &[stake_state_bump]];
+        let rent_lamports = Rent::get()?.minimum_balance(STAKE_STATE_SIZE);
+        let current_lamports = stake_state_info.lamports();
+
+        if current_lamports == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    &user.key(),
+                    &stake_state_address,
+                    rent_lamports,
+                    STAKE_STATE_SIZE as u64,
+                    program_id,
+                ),
+                &[user.clone(), stake_state_info.clone(), system_program.clone()],
+                &[stake_state_seeds],
+            )?;
+        } else {
+            // the address was already funded so create_account would fail, top up the rent then allocate and assign
+            let rent_shortfall = rent_lamports.saturating_sub(current_lamports);
+            if rent_shortfall > 0 {
+                invoke(
+                    &system_instruction::transfer(&user.key(), &stake_state_address, rent_shortfall),
+                    &[user.clone(), stake_state_info.clone(), system_program.clone()],
+                )?;
+            }
+            invoke_signed(
+                &system_instruction::allocate(&stake_state_address, STAKE_STATE_SIZE as u64),
+                &[stake_state_info.clone(), system_program.clone()],
+                &[stake_state_seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(&stake_state_address, program_id),
+                &[stake_state_info.clone(), system_program.clone()],
+                &[stake_state_seeds],
+            )?;
+        }
+
+        let stake_state = StakeState {
+            bump: stake_state_bump,
+            stake_start: 0,
+            resting_level: 0,
+            token_account: Pubkey::default(),
+            original_mint: *original_mint,
+            pool: *pool,
+            achievment_level: None,
+        };
+        let mut data = stake_state_info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data[..];
+        stake_state.try_serialize(&mut writer)?;
+    }
+
+    Account::<StakeState>::try_from(stake_state_info)
+}
+
+#[derive(Accounts)]
+pub struct StakeManyCtx<'info> {
+    #[account(mut)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
+
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        mut,
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    // user
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), user.key().as_ref()],
+        bump,
+        payer = user,
+        space = STAKER_PROFILE_SIZE
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+    /// CHECK: constraint verifies this is the metadata program
+    #[account(constraint =
+        metadata_program.key() == metadata_program_id
+        @ ErrorCode::InvalidMetadataProgram
+    )]
+    pub metadata_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>
+}
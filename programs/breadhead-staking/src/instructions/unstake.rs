@@ -1,91 +1,182 @@
 use {
-    crate::{errors::ErrorCode, state::*},
+    crate::{errors::ErrorCode, events::{UnstakeCooldownStarted, Unstaked}, state::*, utils::{burn_receipt, is_programmable, load_original_mint_metadata, thaw_token_account, transfer_from_escrow, unlock_programmable, validate_escrow_token_account, ProgrammableAccounts}},
     anchor_lang::prelude::*,
     anchor_lang::AccountsClose,
     anchor_spl::token::{Mint, Token, TokenAccount, Revoke, revoke},
     mpl_token_metadata::{
-        instruction::thaw_delegated_account,
         ID as metadata_program_id,
         utils::is_master_edition
     },
-    solana_program::program::invoke_signed
+    solana_program::sysvar::instructions::ID as sysvar_instructions_id,
 };
 
-pub fn handler(ctx: Context<UnstakeCtx>) -> Result<()> {
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeCtx<'info>>, amount: u64) -> Result<()> {
 
-    if ctx.accounts.stake_pool.min_stake_seconds.is_some()
-        && ctx.accounts.stake_pool.min_stake_seconds.unwrap() > 0
-        && ((Clock::get().unwrap().unix_timestamp - ctx.accounts.stake_entry.last_staked_at) as u32) < ctx.accounts.stake_pool.min_stake_seconds.unwrap()
-    {
-        return Err(error!(ErrorCode::MinStakeSecondsNotSatisfied));
+    if amount == 0 || amount > ctx.accounts.stake_entry.amount {
+        return Err(error!(ErrorCode::InvalidUnstakeAmount));
     }
 
-    if ctx.accounts.stake_pool.cooldown_seconds.is_some() && ctx.accounts.stake_pool.cooldown_seconds.unwrap() > 0 {
-        if ctx.accounts.stake_entry.cooldown_start_seconds.is_none() {
-            ctx.accounts.stake_entry.cooldown_start_seconds = Some(Clock::get().unwrap().unix_timestamp);
-            return Ok(());
-        } else if ctx.accounts.stake_entry.cooldown_start_seconds.is_some() && ((Clock::get().unwrap().unix_timestamp - ctx.accounts.stake_entry.cooldown_start_seconds.unwrap()) as u32) < ctx.accounts.stake_pool.cooldown_seconds.unwrap() {
-            return Err(error!(ErrorCode::CooldownSecondRemaining));
-        }
+    if !check_unstake_timers(&ctx.accounts.stake_pool, &mut ctx.accounts.stake_entry)? {
+        emit!(UnstakeCooldownStarted {
+            pool: ctx.accounts.stake_pool.key(),
+            mint: ctx.accounts.original_mint.key(),
+            user: ctx.accounts.user.key(),
+            amount: ctx.accounts.stake_entry.amount,
+            cooldown_start: ctx.accounts.stake_entry.cooldown_start_seconds.unwrap(),
+            total_stake_seconds: ctx.accounts.stake_entry.total_stake_seconds,
+        });
+        return Ok(());
     }
 
-    // thaw token account
-    let thaw_ix = thaw_delegated_account(
-        ctx.accounts.metadata_program.key(),
-        ctx.accounts.program_authority.key(),
-        ctx.accounts.user_original_mint_token_account.key(),
-        ctx.accounts.master_edition.key(),
-        ctx.accounts.original_mint.key()
-    );
-
-    let auth_bump = *ctx.bumps.get("program_authority").unwrap();
-    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
-    let signer = &[&auth_seeds[..]];
-    invoke_signed(
-        &thaw_ix,
-        &[
-            ctx.accounts.metadata_program.to_account_info(),
-            ctx.accounts.program_authority.to_account_info(),
-            ctx.accounts.user_original_mint_token_account.to_account_info(),
-            ctx.accounts.master_edition.to_account_info(),
-            ctx.accounts.original_mint.to_account_info()
-        ],
-        signer
+    // burn the receipts for the unstaked amount
+    burn_receipt(
+        &mut ctx.accounts.stake_entry,
+        ctx.remaining_accounts,
+        &ctx.accounts.user.to_account_info(),
+        &ctx.accounts.token_program.to_account_info(),
+        amount
     )?;
 
-    // revoke program authority as delegate
-    revoke(ctx.accounts.revoke_ctx())?;
+    if ctx.accounts.stake_pool.stake_mode == StakeMode::Escrow as u8 {
+        // return the tokens from escrow to the staker
+        validate_escrow_token_account(&ctx.accounts.escrow_token_account, &ctx.accounts.stake_entry.key(), &ctx.accounts.original_mint.key())?;
+        transfer_from_escrow(
+            &ctx.accounts.escrow_token_account,
+            &ctx.accounts.user_original_mint_token_account.to_account_info(),
+            &ctx.accounts.program_authority,
+            &ctx.accounts.token_program.to_account_info(),
+            *ctx.bumps.get("program_authority").unwrap(),
+            amount
+        )?;
+    } else {
+        let original_mint_metadata = load_original_mint_metadata(&ctx.accounts.original_mint_metadata, &ctx.accounts.original_mint.key())?;
+        if is_programmable(&original_mint_metadata) {
+            // unlock and revoke the staking delegate through the metadata program
+            unlock_programmable(&ctx.accounts.programmable_accounts(), &original_mint_metadata, *ctx.bumps.get("program_authority").unwrap())?;
+        } else {
+            // thaw token account
+            thaw_token_account(
+                &ctx.accounts.metadata_program,
+                &ctx.accounts.program_authority,
+                &ctx.accounts.user_original_mint_token_account.to_account_info(),
+                &ctx.accounts.master_edition,
+                &ctx.accounts.original_mint.to_account_info(),
+                &ctx.accounts.token_program.to_account_info(),
+                *ctx.bumps.get("program_authority").unwrap()
+            )?;
 
-    let stake_pool = &mut ctx.accounts.stake_pool;
-    let stake_entry = &mut ctx.accounts.stake_entry;
+            // revoke program authority as delegate
+            revoke(ctx.accounts.revoke_ctx())?;
+        }
+    }
 
-    stake_entry.total_stake_seconds = stake_entry.total_stake_seconds.saturating_add(
-        (u128::try_from(stake_entry.cooldown_start_seconds.unwrap_or(Clock::get().unwrap().unix_timestamp))
-            .unwrap()
-            .saturating_sub(u128::try_from(stake_entry.last_staked_at).unwrap()))
-        .checked_mul(u128::try_from(stake_entry.amount).unwrap())
-        .unwrap(),
-    );
-    // update state
-    stake_entry.last_staker = Pubkey::default();
-    stake_entry.amount = stake_entry.amount.checked_sub(1).unwrap();
-    stake_entry.cooldown_start_seconds = None;
+    let stake_seconds = record_unstake(&mut ctx.accounts.stake_pool, &mut ctx.accounts.stake_entry, amount);
+    ctx.accounts.staker_profile.record_stake_seconds(stake_seconds);
 
-    stake_pool.total_staked = stake_pool.total_staked.checked_sub(1).unwrap();
+    let close_stake_state = if ctx.accounts.stake_entry.amount == 0 {
+        ctx.accounts.pool_stats.record_unstake(&mut ctx.accounts.staker_profile, stake_seconds, ctx.accounts.stake_state.resting_level);
+        apply_progress_mode(&ctx.accounts.stake_pool, &mut ctx.accounts.stake_state)
+    } else {
+        // a partial unstake keeps the entry and its stake state staked
+        ctx.accounts.pool_stats.record_stake_seconds(ctx.accounts.stake_pool.total_staked, stake_seconds);
+        false
+    };
 
-    ctx.accounts.stake_state.resting_level = 0;
-    ctx.accounts.stake_state.achievment_level = Achievement::DoughBoy;
+    emit!(Unstaked {
+        pool: ctx.accounts.stake_pool.key(),
+        mint: ctx.accounts.original_mint.key(),
+        user: ctx.accounts.user.key(),
+        amount,
+        unstaked_at: Clock::get().unwrap().unix_timestamp,
+        total_stake_seconds: ctx.accounts.stake_entry.total_stake_seconds,
+    });
 
-    // close user stake state account
-    ctx.accounts.stake_state.close(ctx.accounts.user.to_account_info())?;
+    // close user stake state account unless the pool keeps progress
+    if close_stake_state {
+        ctx.accounts.stake_state.close(ctx.accounts.user.to_account_info())?;
+    }
 
     Ok(())
 }
 
+// enforce min stake seconds and cooldown, returns false if this call only started the cooldown
+pub fn check_unstake_timers(stake_pool: &StakePool, stake_entry: &mut StakeEntry) -> Result<bool> {
+    // min stake seconds no longer apply once the pool has ended and entries may have been settled
+    let pool_ended = stake_pool.end_date.is_some() && Clock::get().unwrap().unix_timestamp > stake_pool.end_date.unwrap();
+    if !pool_ended
+        && stake_pool.min_stake_seconds.is_some()
+        && stake_pool.min_stake_seconds.unwrap() > 0
+        && ((Clock::get().unwrap().unix_timestamp - stake_entry.last_staked_at) as u32) < stake_pool.min_stake_seconds.unwrap()
+    {
+        return Err(error!(ErrorCode::MinStakeSecondsNotSatisfied));
+    }
+
+    if let Some(cooldown_seconds) = stake_pool.cooldown_seconds.filter(|cooldown_seconds| *cooldown_seconds > 0) {
+        match stake_entry.cooldown_start_seconds {
+            None => {
+                stake_entry.cooldown_start_seconds = Some(Clock::get().unwrap().unix_timestamp);
+                return Ok(false);
+            }
+            Some(cooldown_start) if ((Clock::get().unwrap().unix_timestamp - cooldown_start) as u32) < cooldown_seconds => {
+                return Err(error!(ErrorCode::CooldownSecondRemaining));
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(true)
+}
+
+// apply the pool progress mode to the stake state, returns true if the state should be closed
+pub fn apply_progress_mode(stake_pool: &StakePool, stake_state: &mut StakeState) -> bool {
+    match stake_pool.progress_mode {
+        m if m == ProgressMode::Preserve as u8 => false,
+        m if m == ProgressMode::Decay as u8 => {
+            stake_state.resting_level = stake_state.resting_level.saturating_sub(stake_pool.progress_decay_levels);
+            // recomputed from the decayed level on the next calculate_reward
+            stake_state.achievment_level = None;
+            false
+        }
+        _ => {
+            stake_state.resting_level = 0;
+            stake_state.achievment_level = None;
+            true
+        }
+    }
+}
+
+// update stake entry and pool once the token is unlocked, returns the stake seconds credited to the entry
+pub fn record_unstake(stake_pool: &mut StakePool, stake_entry: &mut StakeEntry, amount: u64) -> u128 {
+    let now = Clock::get().unwrap().unix_timestamp;
+    let mut stake_seconds = stake_entry.pending_stake_seconds(now, stake_pool.end_date);
+    stake_entry.amount = stake_entry.amount.checked_sub(amount).unwrap();
+    if let Some(cooldown_start) = stake_entry.cooldown_start_seconds.take() {
+        // only the withdrawn amount stops accruing at the cooldown start, the remaining amount accrues until now
+        let accrued_until = stake_pool.end_date.map_or(now, |end_date| now.min(end_date));
+        let cooldown_seconds = accrued_until.saturating_sub(cooldown_start).max(0);
+        stake_seconds = stake_seconds.saturating_add(
+            u128::try_from(cooldown_seconds).unwrap().checked_mul(u128::from(stake_entry.amount)).unwrap()
+        );
+    }
+    stake_entry.total_stake_seconds = stake_entry.total_stake_seconds.saturating_add(stake_seconds);
+    if stake_entry.amount == 0 {
+        stake_entry.last_staker = Pubkey::default();
+    } else {
+        // the remaining amount accrues from now, this also restarts min stake seconds
+        stake_entry.last_staked_at = now;
+    }
+
+    stake_pool.total_staked = stake_pool.total_staked.checked_sub(amount).unwrap();
+
+    stake_seconds
+}
+
 #[derive(Accounts)]
 pub struct UnstakeCtx<'info> {
     #[account(mut, constraint = stake_entry.pool == stake_pool.key() @ ErrorCode::InvalidStakePool)]
     pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
     #[account(mut, seeds = [STAKE_ENTRY_PREFIX.as_bytes(), stake_entry.pool.as_ref(), stake_entry.original_mint.as_ref(), get_stake_seed(original_mint.supply, user.key()).as_ref()], bump=stake_entry.bump)]
     pub stake_entry: Box<Account<'info, StakeEntry>>,
 
@@ -98,27 +189,47 @@ pub struct UnstakeCtx<'info> {
     pub program_authority: AccountInfo<'info>,
     pub original_mint: Box<Account<'info, Mint>>,
 
-    /// CHECK: constraint verifies this is a master edition
+    /// CHECK: constraint verifies this is a master edition, fungible mints have none
     #[account(constraint = 
-        is_master_edition(
-            &master_edition, original_mint.decimals, original_mint.supply) == true
+        original_mint.supply > 1 || is_master_edition(
+            &master_edition, original_mint.decimals, original_mint.supply)
             @ ErrorCode::InvalidMasterEdition
         )]
         pub master_edition: AccountInfo<'info>,
+    /// CHECK: derivation and owner verified in the handler
+    #[account(mut)]
+    pub original_mint_metadata: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts, verified against the token account in the handler
+    #[account(mut)]
+    pub token_record: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts with a rule set, verified against the metadata in the handler
+    pub authorization_rules: AccountInfo<'info>,
+    /// CHECK: only used for programmable nfts with a rule set, verified by the metadata program
+    pub authorization_rules_program: AccountInfo<'info>,
 
     // user
     #[account(mut, constraint = user.key() == stake_entry.last_staker @ ErrorCode::InvalidUnstakeUser)]
     pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), user.key().as_ref()],
+        bump = staker_profile.bump
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
     #[account(
         mut,
         constraint = user_original_mint_token_account.mint == stake_entry.original_mint
         && user_original_mint_token_account.owner == user.key()
         @ ErrorCode::InvalidUserOriginalMintTokenAccount)]
         pub user_original_mint_token_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: only used in escrow mode, verified against the stake entry in the handler
+    #[account(mut)]
+    pub escrow_token_account: AccountInfo<'info>,
     #[account(
         mut,
-        seeds = [user.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
-        bump = stake_state.bump
+        seeds = [user.key().as_ref(), stake_pool.key().as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes()],
+        bump = stake_state.bump,
+        constraint = stake_state.pool == stake_pool.key() @ ErrorCode::InvalidStakePool
     )]
     pub stake_state: Account<'info, StakeState>,
 
@@ -129,7 +240,14 @@ pub struct UnstakeCtx<'info> {
         constraint = metadata_program.key() == metadata_program_id
         @ ErrorCode::InvalidMetadataProgram
     )]
-    pub metadata_program: AccountInfo<'info>
+    pub metadata_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: constraint verifies this is the instructions sysvar
+    #[account(
+        constraint = sysvar_instructions.key() == sysvar_instructions_id
+        @ ErrorCode::InvalidInstructionsSysvar
+    )]
+    pub sysvar_instructions: AccountInfo<'info>
 }
 
 impl<'info> UnstakeCtx <'info> {
@@ -142,4 +260,22 @@ impl<'info> UnstakeCtx <'info> {
 
         CpiContext::new(cpi_program, cpi_accounts)
     }
+
+    pub fn programmable_accounts(&self) -> ProgrammableAccounts<'info> {
+        ProgrammableAccounts {
+            metadata_program: self.metadata_program.to_account_info(),
+            metadata: self.original_mint_metadata.to_account_info(),
+            master_edition: self.master_edition.to_account_info(),
+            token_record: self.token_record.to_account_info(),
+            mint: self.original_mint.to_account_info(),
+            token_account: self.user_original_mint_token_account.to_account_info(),
+            user: self.user.to_account_info(),
+            program_authority: self.program_authority.to_account_info(),
+            authorization_rules_program: self.authorization_rules_program.to_account_info(),
+            authorization_rules: self.authorization_rules.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            sysvar_instructions: self.sysvar_instructions.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+        }
+    }
 }
\ No newline at end of file
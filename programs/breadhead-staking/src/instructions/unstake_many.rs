@@ -0,0 +1,184 @@
+use {
+    crate::{
+        errors::ErrorCode,
+        events::{UnstakeCooldownStarted, Unstaked},
+        instructions::unstake::{apply_progress_mode, check_unstake_timers, record_unstake},
+        state::*,
+        utils::thaw_token_account
+    },
+    anchor_lang::prelude::*,
+    anchor_lang::{AccountsClose, AccountsExit},
+    anchor_spl::token::{Mint, Token, TokenAccount, Revoke, revoke},
+    mpl_token_metadata::{
+        ID as metadata_program_id,
+        utils::is_master_edition
+    },
+};
+
+// remaining accounts are passed in groups of (stake_entry, original_mint, master_edition, token_account, stake_state)
+pub const UNSTAKE_MANY_GROUP_SIZE: usize = 5;
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeManyCtx<'info>>) -> Result<()> {
+
+    // batches only support freeze mode
+    if ctx.accounts.stake_pool.stake_mode != StakeMode::Freeze as u8 {
+        return Err(error!(ErrorCode::InvalidStakeMode));
+    }
+
+    if ctx.remaining_accounts.is_empty() || !ctx.remaining_accounts.chunks_exact(UNSTAKE_MANY_GROUP_SIZE).remainder().is_empty() {
+        return Err(error!(ErrorCode::InvalidRemainingAccounts));
+    }
+
+    let auth_bump = *ctx.bumps.get("program_authority").unwrap();
+    let user = ctx.accounts.user.key();
+
+    for group in ctx.remaining_accounts.chunks(UNSTAKE_MANY_GROUP_SIZE) {
+        let stake_entry_info = &group[0];
+        let original_mint_info = &group[1];
+        let master_edition_info = &group[2];
+        let token_account_info = &group[3];
+        let stake_state_info = &group[4];
+
+        let mut stake_entry = Account::<StakeEntry>::try_from(stake_entry_info)?;
+        let original_mint = Account::<Mint>::try_from(original_mint_info)?;
+        let token_account = Account::<TokenAccount>::try_from(token_account_info)?;
+        let mut stake_state = Account::<StakeState>::try_from(stake_state_info)?;
+
+        // same checks UnstakeCtx applies to a single unstake
+        let stake_entry_address = Pubkey::create_program_address(
+            &[
+                STAKE_ENTRY_PREFIX.as_bytes(),
+                stake_entry.pool.as_ref(),
+                stake_entry.original_mint.as_ref(),
+                get_stake_seed(original_mint.supply, user).as_ref(),
+                &[stake_entry.bump],
+            ],
+            ctx.program_id,
+        ).map_err(|_| error!(ErrorCode::InvalidStakeEntry))?;
+        if stake_entry_address != stake_entry.key() {
+            return Err(error!(ErrorCode::InvalidStakeEntry));
+        }
+        if stake_entry.pool != ctx.accounts.stake_pool.key() {
+            return Err(error!(ErrorCode::InvalidStakePool));
+        }
+        if stake_entry.last_staker != user {
+            return Err(error!(ErrorCode::InvalidUnstakeUser));
+        }
+        // receipts are not burned in batches
+        if stake_entry.stake_mint_claimed {
+            return Err(error!(ErrorCode::InvalidReceiptMint));
+        }
+        if original_mint.key() != stake_entry.original_mint {
+            return Err(error!(ErrorCode::InvalidOriginalMint));
+        }
+        if !is_master_edition(master_edition_info, original_mint.decimals, original_mint.supply) {
+            return Err(error!(ErrorCode::InvalidMasterEdition));
+        }
+        if token_account.mint != stake_entry.original_mint || token_account.owner != user {
+            return Err(error!(ErrorCode::InvalidUserOriginalMintTokenAccount));
+        }
+        let stake_state_address = Pubkey::create_program_address(
+            &[user.as_ref(), stake_entry.pool.as_ref(), original_mint.key().as_ref(), STAKE_STATE_SEED.as_bytes(), &[stake_state.bump]],
+            ctx.program_id,
+        ).map_err(|_| error!(ErrorCode::InvalidStakeState))?;
+        if stake_state_address != stake_state.key() {
+            return Err(error!(ErrorCode::InvalidStakeState));
+        }
+        if stake_state.pool != ctx.accounts.stake_pool.key() {
+            return Err(error!(ErrorCode::InvalidStakePool));
+        }
+
+        if !check_unstake_timers(&ctx.accounts.stake_pool, &mut stake_entry)? {
+            stake_entry.exit(ctx.program_id)?;
+            emit!(UnstakeCooldownStarted {
+                pool: ctx.accounts.stake_pool.key(),
+                mint: original_mint.key(),
+                user,
+                amount: stake_entry.amount,
+                cooldown_start: stake_entry.cooldown_start_seconds.unwrap(),
+                total_stake_seconds: stake_entry.total_stake_seconds,
+            });
+            continue;
+        }
+
+        // thaw token account
+        thaw_token_account(
+            &ctx.accounts.metadata_program,
+            &ctx.accounts.program_authority,
+            token_account_info,
+            master_edition_info,
+            original_mint_info,
+            &ctx.accounts.token_program.to_account_info(),
+            auth_bump
+        )?;
+
+        // revoke program authority as delegate
+        revoke(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Revoke {
+                    source: token_account_info.clone(),
+                    authority: ctx.accounts.user.to_account_info()
+                }
+            )
+        )?;
+
+        let stake_seconds = record_unstake(&mut ctx.accounts.stake_pool, &mut stake_entry, 1);
+        ctx.accounts.pool_stats.record_unstake(&mut ctx.accounts.staker_profile, stake_seconds, stake_state.resting_level);
+        ctx.accounts.staker_profile.record_stake_seconds(stake_seconds);
+        stake_entry.exit(ctx.program_id)?;
+
+        emit!(Unstaked {
+            pool: ctx.accounts.stake_pool.key(),
+            mint: original_mint.key(),
+            user,
+            amount: 1,
+            unstaked_at: Clock::get().unwrap().unix_timestamp,
+            total_stake_seconds: stake_entry.total_stake_seconds,
+        });
+
+        // close user stake state account unless the pool keeps progress
+        if apply_progress_mode(&ctx.accounts.stake_pool, &mut stake_state) {
+            stake_state.close(ctx.accounts.user.to_account_info())?;
+        } else {
+            stake_state.exit(ctx.program_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnstakeManyCtx<'info> {
+    #[account(mut)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+    #[account(mut, seeds = [POOL_STATS_PREFIX.as_bytes(), stake_pool.key().as_ref()], bump = pool_stats.bump)]
+    pub pool_stats: Box<Account<'info, PoolStats>>,
+
+    /// CHECK: Safe this is used a program signer
+    #[account(
+        mut,
+        seeds = [PROGRAM_AUTHORITY_SEED.as_bytes()],
+        bump
+    )]
+    pub program_authority: AccountInfo<'info>,
+
+    // user
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STAKER_PROFILE_PREFIX.as_bytes(), stake_pool.key().as_ref(), user.key().as_ref()],
+        bump = staker_profile.bump
+    )]
+    pub staker_profile: Box<Account<'info, StakerProfile>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+    /// CHECK: constraint verifies this is the metadata program
+    #[account(
+        constraint = metadata_program.key() == metadata_program_id
+        @ ErrorCode::InvalidMetadataProgram
+    )]
+    pub metadata_program: AccountInfo<'info>
+}
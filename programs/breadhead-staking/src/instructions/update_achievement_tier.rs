@@ -0,0 +1,38 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<UpdateAchievementTierCtx>, ix: UpdateAchievementTierIx) -> Result<()> {
+    validate_achievement_tier(ix.threshold, &ix.label, &ix.uri)?;
+
+    let achievement_tier = &mut ctx.accounts.achievement_tier;
+    achievement_tier.threshold = ix.threshold;
+    achievement_tier.label = ix.label;
+    achievement_tier.uri = ix.uri;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateAchievementTierCtx<'info> {
+    #[account(
+        mut,
+        constraint = achievement_tier.pool == stake_pool.key()
+        @ ErrorCode::InvalidAchievementTier
+    )]
+    pub achievement_tier: Account<'info, AchievementTier>,
+    #[account(
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateAchievementTierIx {
+    threshold: u8,
+    label: String,
+    uri: String,
+}
@@ -0,0 +1,99 @@
+use {
+    crate::{errors::ErrorCode, events::PoolUpdated, state::*},
+    anchor_lang::prelude::*,
+};
+
+pub fn handler(ctx: Context<UpdatePoolCtx>, ix: UpdatePoolIx) -> Result<()> {
+    validate_rest_levels(&ix.rest_levels)?;
+    validate_progress_mode(ix.progress_mode, ix.progress_decay_levels)?;
+    validate_emergency_penalty(ix.emergency_forfeit_bps)?;
+    if ix.requires_collections.len() > MAX_REQUIRED_COLLECTIONS {
+        return Err(error!(ErrorCode::TooManyRequiredCollections));
+    }
+
+    let stake_pool = &mut ctx.accounts.stake_pool;
+
+    // do not allow the pool to end underneath tokens that are still staked
+    if stake_pool.total_staked > 0 && ix.end_date.is_some() && ix.end_date.unwrap() < Clock::get().unwrap().unix_timestamp {
+        return Err(error!(ErrorCode::InvalidEndDate));
+    }
+
+    // do not lock tokens that are already staked for longer than the pool promised
+    if stake_pool.total_staked > 0
+        && (ix.min_stake_seconds.unwrap_or(0) > stake_pool.min_stake_seconds.unwrap_or(0)
+            || ix.cooldown_seconds.unwrap_or(0) > stake_pool.cooldown_seconds.unwrap_or(0))
+    {
+        return Err(error!(ErrorCode::CannotIncreaseLockWhileStaked));
+    }
+
+    emit!(PoolUpdated {
+        pool: stake_pool.key(),
+        authority: ctx.accounts.authority.key(),
+        old_requires_collections: stake_pool.requires_collections.clone(),
+        new_requires_collections: ix.requires_collections.clone(),
+        old_requires_authorization: stake_pool.requires_authorization,
+        new_requires_authorization: ix.requires_authorization,
+        old_authorization_merkle_root: stake_pool.authorization_merkle_root,
+        new_authorization_merkle_root: ix.authorization_merkle_root,
+        old_reset_on_stake: stake_pool.reset_on_stake,
+        new_reset_on_stake: ix.reset_on_stake,
+        old_cooldown_seconds: stake_pool.cooldown_seconds,
+        new_cooldown_seconds: ix.cooldown_seconds,
+        old_min_stake_seconds: stake_pool.min_stake_seconds,
+        new_min_stake_seconds: ix.min_stake_seconds,
+        old_end_date: stake_pool.end_date,
+        new_end_date: ix.end_date,
+        old_rest_levels: stake_pool.rest_levels.clone(),
+        new_rest_levels: ix.rest_levels.clone(),
+        old_progress_mode: stake_pool.progress_mode,
+        new_progress_mode: ix.progress_mode,
+        old_progress_decay_levels: stake_pool.progress_decay_levels,
+        new_progress_decay_levels: ix.progress_decay_levels,
+        old_emergency_forfeit_bps: stake_pool.emergency_forfeit_bps,
+        new_emergency_forfeit_bps: ix.emergency_forfeit_bps,
+        old_emergency_level_drop: stake_pool.emergency_level_drop,
+        new_emergency_level_drop: ix.emergency_level_drop,
+    });
+
+    stake_pool.requires_collections = ix.requires_collections;
+    stake_pool.requires_authorization = ix.requires_authorization;
+    stake_pool.authorization_merkle_root = ix.authorization_merkle_root;
+    stake_pool.reset_on_stake = ix.reset_on_stake;
+    stake_pool.cooldown_seconds = ix.cooldown_seconds;
+    stake_pool.min_stake_seconds = ix.min_stake_seconds;
+    stake_pool.end_date = ix.end_date;
+    stake_pool.rest_levels = ix.rest_levels;
+    stake_pool.progress_mode = ix.progress_mode;
+    stake_pool.progress_decay_levels = ix.progress_decay_levels;
+    stake_pool.emergency_forfeit_bps = ix.emergency_forfeit_bps;
+    stake_pool.emergency_level_drop = ix.emergency_level_drop;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolCtx<'info> {
+    #[account(
+        mut,
+        constraint = stake_pool.authority == authority.key()
+        @ ErrorCode::InvalidPoolAuthority
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdatePoolIx {
+    requires_collections: Vec<Pubkey>,
+    requires_authorization: bool,
+    authorization_merkle_root: Option<[u8; 32]>,
+    reset_on_stake: bool,
+    cooldown_seconds: Option<u32>,
+    min_stake_seconds: Option<u32>,
+    end_date: Option<i64>,
+    rest_levels: Vec<i64>,
+    progress_mode: u8,
+    progress_decay_levels: u8,
+    emergency_forfeit_bps: u16,
+    emergency_level_drop: u8,
+}
@@ -1,6 +1,11 @@
+// anchor errors carry their source location and are large by design
+#![allow(clippy::result_large_err)]
+
 pub mod errors;
+pub mod events;
 pub mod instructions;
 pub mod state;
+pub mod utils;
 
 use {anchor_lang::prelude::*, instructions::*};
 
@@ -10,40 +15,116 @@ declare_id!("FpEMdUwx8GAE4kc5BXgP5pKwAv7FstakVU6JLRnT5kmH");
 pub mod breadhead_staking {
     use super::*;
 
+    pub fn init_program_config(ctx: Context<InitProgramConfigCtx>, admins: Vec<Pubkey>) -> Result<()> {
+        init_program_config::handler(ctx, admins)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdminCtx>, new_admin: Pubkey) -> Result<()> {
+        propose_admin::handler(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdminCtx>) -> Result<()> {
+        accept_admin::handler(ctx)
+    }
+
+    pub fn remove_admin(ctx: Context<RemoveAdminCtx>) -> Result<()> {
+        remove_admin::handler(ctx)
+    }
+
     pub fn init_pool(ctx: Context<InitPoolCtx>, ix: InitPoolIx) -> Result<()> {
         init_pool::handler(ctx, ix)
     }
 
-    pub fn init_entry(ctx: Context<InitEntryCtx>, user: Pubkey) -> Result<()> {
-        init_entry::handler(ctx, user)
+    pub fn authorize_mint(ctx: Context<AuthorizeMintCtx>, mint: Pubkey) -> Result<()> {
+        authorize_mint::handler(ctx, mint)
+    }
+
+    pub fn deauthorize_mint(ctx: Context<DeauthorizeMintCtx>) -> Result<()> {
+        deauthorize_mint::handler(ctx)
     }
 
-    pub fn stake(ctx: Context<StakeCtx>, amount: u64) -> Result<()> {
+    pub fn init_entry(ctx: Context<InitEntryCtx>, user: Pubkey, authorization_proof: Option<Vec<[u8; 32]>>) -> Result<()> {
+        init_entry::handler(ctx, user, authorization_proof)
+    }
+
+    pub fn stake<'info>(ctx: Context<'_, '_, '_, 'info, StakeCtx<'info>>, amount: u64) -> Result<()> {
         stake::handler(ctx, amount)
     }
 
-    pub fn unstake(ctx: Context<UnstakeCtx>) -> Result<()> {
-        unstake::handler(ctx)
+    pub fn unstake<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeCtx<'info>>, amount: u64) -> Result<()> {
+        unstake::handler(ctx, amount)
+    }
+
+    pub fn init_stake_mint(ctx: Context<InitStakeMintCtx>) -> Result<()> {
+        init_stake_mint::handler(ctx)
+    }
+
+    pub fn init_stake_escrow(ctx: Context<InitStakeEscrowCtx>) -> Result<()> {
+        init_stake_escrow::handler(ctx)
+    }
+
+    pub fn emergency_unstake<'info>(ctx: Context<'_, '_, '_, 'info, EmergencyUnstakeCtx<'info>>) -> Result<()> {
+        emergency_unstake::handler(ctx)
+    }
+
+    pub fn stake_many<'info>(ctx: Context<'_, '_, '_, 'info, StakeManyCtx<'info>>) -> Result<()> {
+        stake_many::handler(ctx)
+    }
+
+    pub fn unstake_many<'info>(ctx: Context<'_, '_, '_, 'info, UnstakeManyCtx<'info>>) -> Result<()> {
+        unstake_many::handler(ctx)
     }
 
     pub fn calculate_reward(ctx: Context<RewardCtx>) -> Result<()> {
         calculate_reward::handler(ctx)
     }
 
-    // pub fn update_pool(ctx: Context<UpdatePoolCtx>, ix: UpdatePoolIx) -> Result<()> {
-    //     update_pool::handler(ctx, ix)
-    // }
+    pub fn refresh_resting_level(ctx: Context<RefreshRestingLevelCtx>) -> Result<()> {
+        refresh_resting_level::handler(ctx)
+    }
+
+    pub fn settle_entry(ctx: Context<SettleEntryCtx>) -> Result<()> {
+        settle_entry::handler(ctx)
+    }
 
-    // pub fn close_stake_pool(ctx: Context<CloseStakePoolCtx>) -> Result<()> {
-    //     close_stake_pool::handler(ctx)
-    // }
+    pub fn claim_badge(ctx: Context<ClaimBadgeCtx>) -> Result<()> {
+        claim_badge::handler(ctx)
+    }
 
-    // pub fn close_stake_entry(ctx: Context<CloseStakeEntryCtx>) -> Result<()> {
-    //     close_stake_entry::handler(ctx)
-    // }
+    pub fn init_reward_distributor(ctx: Context<InitRewardDistributorCtx>, ix: InitRewardDistributorIx) -> Result<()> {
+        init_reward_distributor::handler(ctx, ix)
+    }
 
-    // pub fn reasssign_stake_entry(ctx: Context<ReassignStakeEntryCtx>, ix: ReassignStakeEntryIx) -> Result<()> {
-    //     reassign_stake_entry::handler(ctx, ix)
-    // }
+    pub fn claim_rewards(ctx: Context<ClaimRewardsCtx>) -> Result<()> {
+        claim_rewards::handler(ctx)
+    }
+
+    pub fn close_reward_distributor(ctx: Context<CloseRewardDistributorCtx>) -> Result<()> {
+        close_reward_distributor::handler(ctx)
+    }
+
+    pub fn update_pool(ctx: Context<UpdatePoolCtx>, ix: UpdatePoolIx) -> Result<()> {
+        update_pool::handler(ctx, ix)
+    }
+
+    pub fn close_stake_pool(ctx: Context<CloseStakePoolCtx>) -> Result<()> {
+        close_stake_pool::handler(ctx)
+    }
+
+    pub fn close_stake_entry(ctx: Context<CloseStakeEntryCtx>) -> Result<()> {
+        close_stake_entry::handler(ctx)
+    }
+
+    pub fn reassign_stake_entry(ctx: Context<ReassignStakeEntryCtx>, ix: ReassignStakeEntryIx) -> Result<()> {
+        reassign_stake_entry::handler(ctx, ix)
+    }
+
+    pub fn init_achievement_tier(ctx: Context<InitAchievementTierCtx>, ix: InitAchievementTierIx) -> Result<()> {
+        init_achievement_tier::handler(ctx, ix)
+    }
+
+    pub fn update_achievement_tier(ctx: Context<UpdateAchievementTierCtx>, ix: UpdateAchievementTierIx) -> Result<()> {
+        update_achievement_tier::handler(ctx, ix)
+    }
 
 }
\ No newline at end of file
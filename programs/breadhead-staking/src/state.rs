@@ -1,27 +1,53 @@
+use crate::errors::ErrorCode;
 use anchor_lang::prelude::*;
-use solana_program::{pubkey, pubkey::Pubkey};
+use anchor_spl::token::TokenAccount;
 
 pub const STAKE_ENTRY_PREFIX: &str = "stake-entry";
 pub const STAKE_ENTRY_SIZE: usize = 8 + std::mem::size_of::<StakeEntry>() + 8;
 
 pub const STAKE_POOL_PREFIX: &str = "stake-pool";
 
+pub const STAKE_MINT_PREFIX: &str = "stake-mint";
+
+pub const STAKE_ESCROW_PREFIX: &str = "stake-escrow";
+
 pub const IDENTIFIER_PREFIX: &str = "identifier";
 pub const IDENTIFIER_SIZE: usize = 8 + std::mem::size_of::<Identifier>() + 8;
 
 pub const STAKE_AUTHORIZATION_PREFIX: &str = "stake-authorization";
 pub const STAKE_AUTHORIZATION_SIZE: usize = 8 + std::mem::size_of::<StakeAuthorizationRecord>() + 8;
 
+pub const REWARD_DISTRIBUTOR_PREFIX: &str = "reward-distributor";
+pub const REWARD_DISTRIBUTOR_SIZE: usize = 8 + std::mem::size_of::<RewardDistributor>() + 8;
+
+
 pub const PROGRAM_AUTHORITY_SEED: &str = "authority";
-// sole authority that can create and/or update staking pools
-pub static CHIEF_BREADHEAD: Pubkey = pubkey!("F1ePX4vnFQjrK7XX9rhSRnriPf4V9hh1Cz1nbNGGrkTJ");
+
+pub const PROGRAM_CONFIG_SEED: &str = "program-config";
+pub const MAX_ADMINS: usize = 5;
+pub const PROGRAM_CONFIG_SIZE: usize = 8 + std::mem::size_of::<ProgramConfig>() + MAX_ADMINS * 32 + 8;
 
 pub const STAKE_STATE_SEED: &str = "state";
 
-// pub const REST_LEVELS: [i64; 5] = [1728000, 3456000, 5184000, 8640000, 12960000];
+pub const MAX_BPS: u16 = 10_000;
+
+pub const MAX_REST_LEVELS: usize = 10;
+
+pub const BADGE_RECORD_PREFIX: &str = "badge-record";
+pub const BADGE_RECORD_SIZE: usize = 8 + std::mem::size_of::<BadgeRecord>() + 8;
+pub const BADGE_MINT_PREFIX: &str = "badge-mint";
+pub const BADGE_SYMBOL: &str = "BREAD";
 
-// test
-pub const REST_LEVELS: [i64; 5] = [1, 2, 3, 4, 5];
+pub const ACHIEVEMENT_TIER_PREFIX: &str = "achievement-tier";
+pub const MAX_TIER_LABEL_LENGTH: usize = 32;
+pub const MAX_TIER_URI_LENGTH: usize = 200;
+pub const ACHIEVEMENT_TIER_SIZE: usize = 8 + std::mem::size_of::<AchievementTier>() + MAX_TIER_LABEL_LENGTH + MAX_TIER_URI_LENGTH + 8;
+
+pub const POOL_STATS_PREFIX: &str = "pool-stats";
+pub const POOL_STATS_SIZE: usize = 8 + std::mem::size_of::<PoolStats>() + 8;
+
+pub const STAKER_PROFILE_PREFIX: &str = "staker-profile";
+pub const STAKER_PROFILE_SIZE: usize = 8 + std::mem::size_of::<StakerProfile>() + 8;
 
 
 #[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
@@ -43,22 +69,70 @@ pub struct StakeEntry {
     pub total_stake_seconds: u128,
     pub stake_mint_claimed: bool,
     pub kind: u8,
+    // receipts are opt-in per entry, entries without a stake mint stake without one
     pub stake_mint: Option<Pubkey>,
     pub cooldown_start_seconds: Option<i64>,
+    pub payer: Pubkey,
+    // stake seconds already paid out, lowered together with total_stake_seconds
+    pub reward_seconds_claimed: u128,
 }
 
-pub const STAKE_POOL_SIZE: usize = 8 + std::mem::size_of::<StakePool>() + 8;
+impl StakeEntry {
+    // seconds staked since last_staked_at that are not yet included in total_stake_seconds, accrual stops at the pool end date
+    pub fn pending_stake_seconds(&self, now: i64, end_date: Option<i64>) -> u128 {
+        let accrued_until = self.cooldown_start_seconds.unwrap_or(now);
+        let accrued_until = end_date.map_or(accrued_until, |end_date| accrued_until.min(end_date));
+        (u128::try_from(accrued_until)
+            .unwrap()
+            .saturating_sub(u128::try_from(self.last_staked_at).unwrap()))
+        .checked_mul(u128::from(self.amount))
+        .unwrap()
+    }
+}
+
+pub const MAX_REQUIRED_COLLECTIONS: usize = 10;
+pub const STAKE_POOL_SIZE: usize = 8 + std::mem::size_of::<StakePool>() + MAX_REQUIRED_COLLECTIONS * 32 + MAX_REST_LEVELS * 8 + 8;
 #[account]
 pub struct StakePool {
     pub bump: u8,
     pub authority: Pubkey,
-    // pub requires_collections: Vec<Pubkey>,
+    pub requires_collections: Vec<Pubkey>,
     pub requires_authorization: bool,
+    // root of a merkle tree of allowed mints, checked against a proof in init_entry
+    pub authorization_merkle_root: Option<[u8; 32]>,
     pub reset_on_stake: bool,
     pub total_staked: u64,
     pub cooldown_seconds: Option<u32>,
     pub min_stake_seconds: Option<u32>,
     pub end_date: Option<i64>,
+    // ordered stake duration thresholds in seconds for each resting level
+    // production thresholds: [1728000, 3456000, 5184000, 8640000, 12960000]
+    pub rest_levels: Vec<i64>,
+    pub achievement_tier_count: u8,
+    // how resting level progress is handled on unstake, see ProgressMode
+    pub progress_mode: u8,
+    // resting levels dropped on unstake in decay mode
+    pub progress_decay_levels: u8,
+    // penalty for emergency_unstake: share of stake seconds forfeited and resting levels dropped
+    pub emergency_forfeit_bps: u16,
+    pub emergency_level_drop: u8,
+    // how staked tokens are locked, see StakeMode
+    pub stake_mode: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+#[repr(u8)]
+pub enum StakeMode {
+    Freeze = 0, // token is delegated and frozen in the staker's wallet
+    Escrow = 1, // token is transferred to a program owned account keyed by the stake entry
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+#[repr(u8)]
+pub enum ProgressMode {
+    Reset = 0,    // stake state is closed on unstake
+    Preserve = 1, // resting level is kept and resumed on re-stake
+    Decay = 2,    // resting level drops by progress_decay_levels on unstake
 }
 
 pub const STAKE_STATE_SIZE: usize = 8 + std::mem::size_of::<StakeState>() + 8;
@@ -70,7 +144,141 @@ pub struct StakeState {
     pub token_account: Pubkey,
     pub original_mint: Pubkey,
     pub pool: Pubkey,
-    pub achievment_level: Achievement,
+    // index of the highest achievement tier reached, if any
+    pub achievment_level: Option<u8>,
+}
+
+#[account]
+pub struct AchievementTier {
+    pub bump: u8,
+    pub pool: Pubkey,
+    pub index: u8,
+    // minimum resting level required to reach this tier
+    pub threshold: u8,
+    pub label: String,
+    pub uri: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+#[repr(u8)]
+pub enum RewardDistributorKind {
+    Mint = 0,     // distributor is the reward mint authority
+    Treasury = 1, // distributor pays out of a pre-funded token account
+}
+
+#[account]
+pub struct RewardDistributor {
+    pub bump: u8,
+    pub stake_pool: Pubkey,
+    pub kind: u8,
+    pub authority: Pubkey,
+    pub reward_mint: Pubkey,
+    // reward tokens paid per stake second
+    pub reward_rate: u64,
+    pub max_supply: Option<u64>,
+    pub rewards_issued: u64,
+}
+
+// singleton holding the admins that can create staking pools
+#[account]
+pub struct ProgramConfig {
+    pub bump: u8,
+    pub admins: Vec<Pubkey>,
+    pub pending_admin: Option<Pubkey>,
+    // admin replaced by the pending admin once the transfer is accepted
+    pub pending_admin_proposer: Option<Pubkey>,
+}
+
+// marks that a wallet has claimed the badge for a pool achievement tier
+#[account]
+pub struct BadgeRecord {
+    pub bump: u8,
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub tier: u8,
+    pub mint: Pubkey,
+}
+
+// pool wide aggregates for dashboards
+#[account]
+pub struct PoolStats {
+    pub bump: u8,
+    pub pool: Pubkey,
+    // wallets with at least one token currently staked
+    pub unique_stakers: u64,
+    pub total_stake_seconds: u128,
+    pub peak_staked: u64,
+    // current stake states per resting level, indexed 0..=MAX_REST_LEVELS
+    pub level_histogram: [u64; MAX_REST_LEVELS + 1],
+}
+
+impl PoolStats {
+    pub fn record_stake(&mut self, staker_profile: &mut StakerProfile, total_staked: u64, stake_seconds: u128, resting_level: u8) {
+        if staker_profile.staked_count == 0 {
+            self.unique_stakers = self.unique_stakers.saturating_add(1);
+        }
+        staker_profile.staked_count = staker_profile.staked_count.saturating_add(1);
+        self.total_stake_seconds = self.total_stake_seconds.saturating_add(stake_seconds);
+        self.peak_staked = self.peak_staked.max(total_staked);
+        self.level_histogram[resting_level as usize] = self.level_histogram[resting_level as usize].saturating_add(1);
+    }
+
+    pub fn record_unstake(&mut self, staker_profile: &mut StakerProfile, stake_seconds: u128, resting_level: u8) {
+        staker_profile.staked_count = staker_profile.staked_count.saturating_sub(1);
+        if staker_profile.staked_count == 0 {
+            self.unique_stakers = self.unique_stakers.saturating_sub(1);
+        }
+        self.total_stake_seconds = self.total_stake_seconds.saturating_add(stake_seconds);
+        self.level_histogram[resting_level as usize] = self.level_histogram[resting_level as usize].saturating_sub(1);
+    }
+
+    // stake seconds settled while the entry stays staked, on top ups and partial unstakes
+    pub fn record_stake_seconds(&mut self, total_staked: u64, stake_seconds: u128) {
+        self.total_stake_seconds = self.total_stake_seconds.saturating_add(stake_seconds);
+        self.peak_staked = self.peak_staked.max(total_staked);
+    }
+
+    pub fn forfeit_stake_seconds(&mut self, stake_seconds: u128) {
+        self.total_stake_seconds = self.total_stake_seconds.saturating_sub(stake_seconds);
+    }
+
+    pub fn record_level_change(&mut self, old_resting_level: u8, new_resting_level: u8) {
+        self.level_histogram[old_resting_level as usize] = self.level_histogram[old_resting_level as usize].saturating_sub(1);
+        self.level_histogram[new_resting_level as usize] = self.level_histogram[new_resting_level as usize].saturating_add(1);
+    }
+}
+
+// lifetime history of a wallet in a pool, survives unstake
+#[account]
+pub struct StakerProfile {
+    pub bump: u8,
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    // entries currently staked
+    pub staked_count: u64,
+    // stake seconds settled on stake and unstake
+    pub lifetime_stake_seconds: u128,
+    pub highest_resting_level: u8,
+    // tier index reached at the highest resting level
+    pub highest_achievement: Option<u8>,
+    pub first_staked_at: i64,
+}
+
+impl StakerProfile {
+    pub fn record_stake_seconds(&mut self, stake_seconds: u128) {
+        self.lifetime_stake_seconds = self.lifetime_stake_seconds.saturating_add(stake_seconds);
+    }
+
+    pub fn forfeit_stake_seconds(&mut self, stake_seconds: u128) {
+        self.lifetime_stake_seconds = self.lifetime_stake_seconds.saturating_sub(stake_seconds);
+    }
+
+    pub fn record_levels(&mut self, resting_level: u8, achievement_level: Option<u8>) {
+        if resting_level >= self.highest_resting_level && achievement_level.is_some() {
+            self.highest_achievement = achievement_level;
+        }
+        self.highest_resting_level = self.highest_resting_level.max(resting_level);
+    }
 }
 
 #[account]
@@ -94,35 +302,115 @@ pub fn get_stake_seed(supply: u64, user: Pubkey) -> Pubkey {
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub enum Achievement {
-    DoughBoy,
-    SixtyNineBadge,
-    BagHolder,
-    MoonShot,
-    Loaf,
-    BreadGetter
+// hashing is shared with the off-chain tree builder so roots and proofs always match
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], mint: &Pubkey) -> bool {
+    breadhead_merkle::verify(proof, root, mint)
 }
 
-pub fn derive_resting_level(stake_start_time: i64) -> u8 {
-    // subtract start time from current time
-    let stake_duration = Clock::get().unwrap().unix_timestamp - stake_start_time;
-    let mut resting_level: u8 = 0;
+pub fn validate_rest_levels(rest_levels: &[i64]) -> Result<()> {
+    if rest_levels.is_empty() || rest_levels.len() > MAX_REST_LEVELS {
+        return Err(error!(ErrorCode::InvalidRestLevels));
+    }
+    // thresholds must be positive and strictly increasing
+    if rest_levels[0] <= 0 || rest_levels.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(error!(ErrorCode::InvalidRestLevels));
+    }
+    Ok(())
+}
+
+pub fn derive_resting_level(stake_start_time: i64, rest_levels: &[i64], end_date: Option<i64>) -> u8 {
+    // subtract start time from current time, levels stop growing at the pool end date
+    let now = Clock::get().unwrap().unix_timestamp;
+    let stake_duration = end_date.map_or(now, |end_date| now.min(end_date)) - stake_start_time;
 
     msg!("Stake start: {}", stake_start_time);
     msg!("Current: {}", Clock::get().unwrap().unix_timestamp);
     msg!("Stake duration: {}", stake_duration);
 
-    // determine where time length falls in the RESTING_LEVELS array
-    for i in 0..REST_LEVELS.len() {
-        if stake_duration < REST_LEVELS[i] {
-            resting_level = i as u8;
-            break;
+    // resting level is the number of pool thresholds the stake duration has reached
+    let resting_level = rest_levels
+        .iter()
+        .take_while(|threshold| stake_duration >= **threshold)
+        .count() as u8;
+
+    msg!("Rest level: {}", resting_level);
+    resting_level
+}
+
+pub fn validate_progress_mode(progress_mode: u8, progress_decay_levels: u8) -> Result<()> {
+    match progress_mode {
+        m if m == ProgressMode::Reset as u8 || m == ProgressMode::Preserve as u8 => Ok(()),
+        m if m == ProgressMode::Decay as u8 && progress_decay_levels > 0 => Ok(()),
+        _ => Err(error!(ErrorCode::InvalidProgressMode)),
+    }
+}
+
+pub fn validate_stake_mode(stake_mode: u8) -> Result<()> {
+    if stake_mode != StakeMode::Freeze as u8 && stake_mode != StakeMode::Escrow as u8 {
+        return Err(error!(ErrorCode::InvalidStakeMode));
+    }
+    Ok(())
+}
+
+pub fn find_stake_escrow_address(stake_entry: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_ESCROW_PREFIX.as_bytes(), stake_entry.as_ref()], &crate::ID)
+}
+
+// the staked token is either in the user's wallet or, in escrow mode, in the stake entry escrow
+pub fn holds_staked_token(stake_pool: &StakePool, stake_entry: &Account<StakeEntry>, token_account: &Account<TokenAccount>, user: &Pubkey) -> bool {
+    if token_account.amount == 0 || token_account.mint != stake_entry.original_mint {
+        return false;
+    }
+    if token_account.owner == *user {
+        return true;
+    }
+    stake_pool.stake_mode == StakeMode::Escrow as u8
+        && stake_entry.last_staker == *user
+        && token_account.key() == find_stake_escrow_address(&stake_entry.key()).0
+}
+
+pub fn validate_emergency_penalty(emergency_forfeit_bps: u16) -> Result<()> {
+    if emergency_forfeit_bps > MAX_BPS {
+        return Err(error!(ErrorCode::InvalidEmergencyPenalty));
+    }
+    Ok(())
+}
+
+// backdate the stake start so derive_resting_level picks up from a preserved resting level
+pub fn resume_stake_start(now: i64, resting_level: u8, rest_levels: &[i64]) -> i64 {
+    let level = (resting_level as usize).min(rest_levels.len());
+    if level == 0 {
+        return now;
+    }
+    now.saturating_sub(rest_levels[level - 1])
+}
+
+pub fn validate_achievement_tier(threshold: u8, label: &str, uri: &str) -> Result<()> {
+    if threshold as usize > MAX_REST_LEVELS || label.len() > MAX_TIER_LABEL_LENGTH || uri.len() > MAX_TIER_URI_LENGTH {
+        return Err(error!(ErrorCode::InvalidAchievementTier));
+    }
+    Ok(())
+}
+
+pub fn derive_achievement_level(pool: &Pubkey, tier_count: u8, resting_level: u8, tier_infos: &[AccountInfo]) -> Result<Option<u8>> {
+    // every tier of the pool must be provided, in index order
+    if tier_infos.len() != tier_count as usize {
+        return Err(error!(ErrorCode::InvalidAchievementTier));
+    }
+
+    // pick the tier with the highest threshold the resting level has reached
+    let mut achievement: Option<(u8, u8)> = None;
+    for (i, tier_info) in tier_infos.iter().enumerate() {
+        let tier = match Account::<AchievementTier>::try_from(tier_info) {
+            Ok(tier) => tier,
+            Err(_) => return Err(error!(ErrorCode::InvalidAchievementTier)),
+        };
+        if tier.pool != *pool || tier.index as usize != i {
+            return Err(error!(ErrorCode::InvalidAchievementTier));
         }
-        else if stake_duration > REST_LEVELS[i] && i == 4 {
-            resting_level = i as u8 + 1;
+        if tier.threshold <= resting_level && !matches!(achievement, Some((threshold, _)) if tier.threshold < threshold) {
+            achievement = Some((tier.threshold, tier.index));
         }
     }
-    msg!("Rest level: {}", resting_level);
-    return resting_level
-}
\ No newline at end of file
+    Ok(achievement.map(|(_, index)| index))
+}
@@ -0,0 +1,423 @@
+use {
+    crate::{errors::ErrorCode, state::*},
+    anchor_lang::prelude::*,
+    anchor_spl::token::{TokenAccount, MintTo, mint_to, Burn, burn, Transfer, transfer, CloseAccount, close_account},
+    mpl_token_metadata::{
+        instruction::{
+            builders::{DelegateBuilder, LockBuilder, RevokeBuilder, UnlockBuilder},
+            freeze_delegated_account, thaw_delegated_account,
+            DelegateArgs, InstructionBuilder, LockArgs, RevokeArgs, UnlockArgs
+        },
+        pda::find_token_record_account,
+        state::{Metadata, ProgrammableConfig, TokenStandard, PREFIX},
+        utils::assert_derivation,
+    },
+    solana_program::program::{invoke, invoke_signed}
+};
+
+// freeze a token account the program authority has been approved as delegate of
+pub fn freeze_token_account<'info>(
+    metadata_program: &AccountInfo<'info>,
+    program_authority: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    master_edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    auth_bump: u8,
+) -> Result<()> {
+    let freeze_ix = freeze_delegated_account(
+        metadata_program.key(),
+        program_authority.key(),
+        token_account.key(),
+        master_edition.key(),
+        mint.key()
+    );
+
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    invoke_signed(
+        &freeze_ix,
+        &[
+            metadata_program.clone(),
+            program_authority.clone(),
+            token_account.clone(),
+            master_edition.clone(),
+            mint.clone(),
+            token_program.clone()
+        ],
+        signer
+    )?;
+
+    Ok(())
+}
+
+// thaw a token account frozen by freeze_token_account
+pub fn thaw_token_account<'info>(
+    metadata_program: &AccountInfo<'info>,
+    program_authority: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    master_edition: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    auth_bump: u8,
+) -> Result<()> {
+    let thaw_ix = thaw_delegated_account(
+        metadata_program.key(),
+        program_authority.key(),
+        token_account.key(),
+        master_edition.key(),
+        mint.key()
+    );
+
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    invoke_signed(
+        &thaw_ix,
+        &[
+            metadata_program.clone(),
+            program_authority.clone(),
+            token_account.clone(),
+            master_edition.clone(),
+            mint.clone(),
+            token_program.clone()
+        ],
+        signer
+    )?;
+
+    Ok(())
+}
+
+// receipt accounts follow the instruction accounts as (stake_mint, user_stake_mint_token_account)
+fn receipt_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+    stake_mint: Pubkey,
+    user: &Pubkey,
+) -> Result<(&'a AccountInfo<'info>, &'a AccountInfo<'info>)> {
+    if remaining_accounts.len() < 2 || remaining_accounts[0].key() != stake_mint {
+        return Err(error!(ErrorCode::InvalidReceiptMint));
+    }
+    let user_stake_mint_token_account = Account::<TokenAccount>::try_from(&remaining_accounts[1])?;
+    if user_stake_mint_token_account.mint != stake_mint || user_stake_mint_token_account.owner != *user {
+        return Err(error!(ErrorCode::InvalidReceiptMint));
+    }
+    Ok((&remaining_accounts[0], &remaining_accounts[1]))
+}
+
+// mint receipts for the staked amount if the entry has a stake mint
+pub fn mint_receipt<'info>(
+    stake_entry: &mut StakeEntry,
+    remaining_accounts: &[AccountInfo<'info>],
+    user: &Pubkey,
+    program_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    auth_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let stake_mint = match stake_entry.stake_mint {
+        Some(stake_mint) => stake_mint,
+        None => return Ok(()),
+    };
+    let (stake_mint_info, user_stake_mint_token_account) = receipt_accounts(remaining_accounts, stake_mint, user)?;
+
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            MintTo {
+                mint: stake_mint_info.clone(),
+                to: user_stake_mint_token_account.clone(),
+                authority: program_authority.clone()
+            },
+            signer
+        ),
+        amount
+    )?;
+    stake_entry.stake_mint_claimed = true;
+
+    Ok(())
+}
+
+// burn receipts for the unstaked amount, the staker must still hold them to unstake
+pub fn burn_receipt<'info>(
+    stake_entry: &mut StakeEntry,
+    remaining_accounts: &[AccountInfo<'info>],
+    user: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if !stake_entry.stake_mint_claimed {
+        return Ok(());
+    }
+    let (stake_mint_info, user_stake_mint_token_account) = receipt_accounts(remaining_accounts, stake_entry.stake_mint.unwrap(), &user.key())?;
+
+    burn(
+        CpiContext::new(
+            token_program.clone(),
+            Burn {
+                mint: stake_mint_info.clone(),
+                from: user_stake_mint_token_account.clone(),
+                authority: user.clone()
+            }
+        ),
+        amount
+    )?;
+    // receipts are outstanding until the whole entry is unstaked
+    stake_entry.stake_mint_claimed = stake_entry.amount > amount;
+
+    Ok(())
+}
+
+// escrow account must be the stake entry's token account for the original mint
+pub fn validate_escrow_token_account(escrow_token_account: &AccountInfo, stake_entry: &Pubkey, original_mint: &Pubkey) -> Result<()> {
+    if escrow_token_account.key() != find_stake_escrow_address(stake_entry).0 {
+        return Err(error!(ErrorCode::InvalidEscrowTokenAccount));
+    }
+    let escrow = Account::<TokenAccount>::try_from(escrow_token_account)?;
+    if escrow.mint != *original_mint {
+        return Err(error!(ErrorCode::InvalidEscrowTokenAccount));
+    }
+    Ok(())
+}
+
+// move tokens out of a stake entry escrow, signed by the program authority
+pub fn transfer_from_escrow<'info>(
+    escrow_token_account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    program_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    auth_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: escrow_token_account.clone(),
+                to: destination.clone(),
+                authority: program_authority.clone()
+            },
+            signer
+        ),
+        amount
+    )
+}
+
+// close an empty stake entry escrow, signed by the program authority
+pub fn close_escrow<'info>(
+    escrow_token_account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    program_authority: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    auth_bump: u8,
+) -> Result<()> {
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    close_account(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            CloseAccount {
+                account: escrow_token_account.clone(),
+                destination: destination.clone(),
+                authority: program_authority.clone()
+            },
+            signer
+        )
+    )
+}
+
+// read the original mint metadata, verifying its derivation and owner
+pub fn load_original_mint_metadata(metadata: &AccountInfo, original_mint: &Pubkey) -> Result<Metadata> {
+    assert_derivation(
+        &mpl_token_metadata::id(),
+        metadata,
+        &[
+            PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            original_mint.as_ref(),
+        ],
+    )?;
+    if metadata.owner.key() != mpl_token_metadata::id() {
+        return Err(error!(ErrorCode::InvalidMintMetadataOwner));
+    }
+    let mint_metadata_data = metadata.try_borrow_data()?;
+    let original_mint_metadata = Metadata::deserialize(&mut mint_metadata_data.as_ref()).map_err(|_| error!(ErrorCode::InvalidMintMetadata))?;
+    if original_mint_metadata.mint != *original_mint {
+        return Err(error!(ErrorCode::InvalidMintMetadata));
+    }
+    Ok(original_mint_metadata)
+}
+
+pub fn is_programmable(metadata: &Metadata) -> bool {
+    metadata.token_standard == Some(TokenStandard::ProgrammableNonFungible)
+}
+
+// accounts the metadata program needs to lock and unlock a programmable nft
+pub struct ProgrammableAccounts<'info> {
+    pub metadata_program: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+    pub token_record: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub user: AccountInfo<'info>,
+    pub program_authority: AccountInfo<'info>,
+    pub authorization_rules_program: AccountInfo<'info>,
+    pub authorization_rules: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub sysvar_instructions: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+impl<'info> ProgrammableAccounts<'info> {
+    // token record must belong to the token account, rules must match the metadata rule set
+    fn rule_set(&self, metadata: &Metadata) -> Result<Option<Pubkey>> {
+        if self.token_record.key() != find_token_record_account(&self.mint.key(), &self.token_account.key()).0 {
+            return Err(error!(ErrorCode::InvalidTokenRecord));
+        }
+        let rule_set = match metadata.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+            None => None,
+        };
+        if rule_set.is_some() && rule_set != Some(self.authorization_rules.key()) {
+            return Err(error!(ErrorCode::InvalidAuthorizationRules));
+        }
+        Ok(rule_set)
+    }
+
+    fn account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![
+            self.metadata_program.clone(),
+            self.metadata.clone(),
+            self.master_edition.clone(),
+            self.token_record.clone(),
+            self.mint.clone(),
+            self.token_account.clone(),
+            self.user.clone(),
+            self.program_authority.clone(),
+            self.authorization_rules_program.clone(),
+            self.authorization_rules.clone(),
+            self.system_program.clone(),
+            self.sysvar_instructions.clone(),
+            self.token_program.clone(),
+        ]
+    }
+}
+
+// delegate a programmable nft to the program authority as staking delegate and lock it
+pub fn lock_programmable(accounts: &ProgrammableAccounts, metadata: &Metadata, auth_bump: u8) -> Result<()> {
+    let rule_set = accounts.rule_set(metadata)?;
+
+    let mut delegate_builder = DelegateBuilder::new();
+    delegate_builder
+        .delegate(accounts.program_authority.key())
+        .metadata(accounts.metadata.key())
+        .master_edition(accounts.master_edition.key())
+        .token_record(accounts.token_record.key())
+        .mint(accounts.mint.key())
+        .token(accounts.token_account.key())
+        .authority(accounts.user.key())
+        .payer(accounts.user.key())
+        .system_program(accounts.system_program.key())
+        .sysvar_instructions(accounts.sysvar_instructions.key())
+        .spl_token_program(accounts.token_program.key());
+    if let Some(rule_set) = rule_set {
+        delegate_builder
+            .authorization_rules_program(accounts.authorization_rules_program.key())
+            .authorization_rules(rule_set);
+    }
+    let delegate_ix = delegate_builder
+        .build(DelegateArgs::StakingV1 { amount: 1, authorization_data: None })
+        .unwrap()
+        .instruction();
+    invoke(&delegate_ix, &accounts.account_infos())?;
+
+    let mut lock_builder = LockBuilder::new();
+    lock_builder
+        .authority(accounts.program_authority.key())
+        .token_owner(accounts.user.key())
+        .token(accounts.token_account.key())
+        .mint(accounts.mint.key())
+        .metadata(accounts.metadata.key())
+        .edition(accounts.master_edition.key())
+        .token_record(accounts.token_record.key())
+        .payer(accounts.user.key())
+        .system_program(accounts.system_program.key())
+        .sysvar_instructions(accounts.sysvar_instructions.key())
+        .spl_token_program(accounts.token_program.key());
+    if let Some(rule_set) = rule_set {
+        lock_builder
+            .authorization_rules_program(accounts.authorization_rules_program.key())
+            .authorization_rules(rule_set);
+    }
+    let lock_ix = lock_builder
+        .build(LockArgs::V1 { authorization_data: None })
+        .unwrap()
+        .instruction();
+
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    invoke_signed(&lock_ix, &accounts.account_infos(), signer)?;
+
+    Ok(())
+}
+
+// unlock a programmable nft locked by lock_programmable and revoke the staking delegate
+pub fn unlock_programmable(accounts: &ProgrammableAccounts, metadata: &Metadata, auth_bump: u8) -> Result<()> {
+    let rule_set = accounts.rule_set(metadata)?;
+
+    let mut unlock_builder = UnlockBuilder::new();
+    unlock_builder
+        .authority(accounts.program_authority.key())
+        .token_owner(accounts.user.key())
+        .token(accounts.token_account.key())
+        .mint(accounts.mint.key())
+        .metadata(accounts.metadata.key())
+        .edition(accounts.master_edition.key())
+        .token_record(accounts.token_record.key())
+        .payer(accounts.user.key())
+        .system_program(accounts.system_program.key())
+        .sysvar_instructions(accounts.sysvar_instructions.key())
+        .spl_token_program(accounts.token_program.key());
+    if let Some(rule_set) = rule_set {
+        unlock_builder
+            .authorization_rules_program(accounts.authorization_rules_program.key())
+            .authorization_rules(rule_set);
+    }
+    let unlock_ix = unlock_builder
+        .build(UnlockArgs::V1 { authorization_data: None })
+        .unwrap()
+        .instruction();
+
+    let auth_seeds = &[PROGRAM_AUTHORITY_SEED.as_bytes(), &[auth_bump]];
+    let signer = &[&auth_seeds[..]];
+    invoke_signed(&unlock_ix, &accounts.account_infos(), signer)?;
+
+    let mut revoke_builder = RevokeBuilder::new();
+    revoke_builder
+        .delegate(accounts.program_authority.key())
+        .metadata(accounts.metadata.key())
+        .master_edition(accounts.master_edition.key())
+        .token_record(accounts.token_record.key())
+        .mint(accounts.mint.key())
+        .token(accounts.token_account.key())
+        .authority(accounts.user.key())
+        .payer(accounts.user.key())
+        .system_program(accounts.system_program.key())
+        .sysvar_instructions(accounts.sysvar_instructions.key())
+        .spl_token_program(accounts.token_program.key());
+    if let Some(rule_set) = rule_set {
+        revoke_builder
+            .authorization_rules_program(accounts.authorization_rules_program.key())
+            .authorization_rules(rule_set);
+    }
+    let revoke_ix = revoke_builder
+        .build(RevokeArgs::StakingV1)
+        .unwrap()
+        .instruction();
+    invoke(&revoke_ix, &accounts.account_infos())?;
+
+    Ok(())
+}
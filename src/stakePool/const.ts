@@ -7,6 +7,28 @@ export const STAKE_POOL_SEED = "stake-pool"
 
 export const STAKE_ENTRY_SEED = "stake-entry"
 
+export const STAKE_MINT_SEED = "stake-mint"
+
+export const STAKE_ESCROW_SEED = "stake-escrow"
+
+export const STAKE_AUTHORIZATION_SEED = "stake-authorization"
+
+export const PROGRAM_CONFIG_SEED = "program-config"
+
+export const ACHIEVEMENT_TIER_SEED = "achievement-tier"
+
+export const BADGE_RECORD_SEED = "badge-record"
+
+export const BADGE_MINT_SEED = "badge-mint"
+
+export const REWARD_DISTRIBUTOR_SEED = "reward-distributor"
+
+
+export const POOL_STATS_SEED = "pool-stats"
+
+export const STAKER_PROFILE_SEED = "staker-profile"
+
 export const masterEditionSeed = "edition"
+export const tokenRecordSeed = "token_record"
 export const metadataSeed = "metadata"
 export const STAKE_PROGRAM_ADDRESS = new PublicKey("FpEMdUwx8GAE4kc5BXgP5pKwAv7FstakVU6JLRnT5kmH")
\ No newline at end of file
@@ -1,7 +1,7 @@
 import * as web3 from '@solana/web3.js'
-import { IDENTIFIER_SEED, STAKE_PROGRAM_ADDRESS, STAKE_POOL_SEED, masterEditionSeed, metadataSeed } from './const'
+import { IDENTIFIER_SEED, STAKE_PROGRAM_ADDRESS, STAKE_POOL_SEED, masterEditionSeed, metadataSeed, tokenRecordSeed } from './const'
 import { BN } from "@project-serum/anchor"
-import { TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo } from "@solana/spl-token"
+import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, getAssociatedTokenAddress, mintTo } from "@solana/spl-token"
 import {
     createCreateMasterEditionV3Instruction,
     CreateMetadataAccountInstructionArgs,
@@ -11,8 +11,13 @@ import {
     CreateMasterEditionV3InstructionAccounts,
     CreateMasterEditionV3InstructionArgs,
     DataV2,
+    createCreateInstruction,
+    createMintInstruction,
+    createVerifyCollectionInstruction,
+    TokenStandard,
     PROGRAM_ID as METADATA_PROGRAM_ID
 } from "@metaplex-foundation/mpl-token-metadata"
+import { keccak_256 } from "@noble/hashes/sha3"
 
 /**
  * Finds the identifier id.
@@ -85,7 +90,8 @@ export const createNFTMint = async (
 export const createMasterEditionTxs = async (
     mintId: web3.PublicKey,
     tokenCreatorId: web3.Keypair,
-    connection: web3.Connection
+    connection: web3.Connection,
+    collection: web3.PublicKey | null = null
     ):  Promise<[web3.PublicKey, web3.PublicKey]> => {
     const tx = new web3.Transaction()
 
@@ -114,7 +120,8 @@ export const createMasterEditionTxs = async (
         uri: "http://test/",
         sellerFeeBasisPoints: 10,
         creators: [creator],
-        collection: null,
+        // the collection stays unverified until verifyCollection is called
+        collection: collection ? { key: collection, verified: false } : null,
         uses: null,
     }
     const metadataArgs: CreateMetadataAccountInstructionArgs = {
@@ -157,10 +164,144 @@ export const createMasterEditionTxs = async (
     tx.add(masterEditionIx)
 
     const signature = await connection.sendTransaction(tx, [tokenCreatorId])
+    await connection.confirmTransaction(signature, "confirmed")
 
     return [metadataId, masterEditionId]
 }
 
+/**
+ * Verify the collection of an nft created with createMasterEditionTxs
+ * @param connection
+ * @param collectionAuthority update authority of the collection nft
+ * @param metadata metadata of the nft in the collection
+ * @param collectionMint
+ * @param collectionMetadata
+ * @param collectionMasterEdition
+ */
+export const verifyCollection = async (
+    connection: web3.Connection,
+    collectionAuthority: web3.Keypair,
+    metadata: web3.PublicKey,
+    collectionMint: web3.PublicKey,
+    collectionMetadata: web3.PublicKey,
+    collectionMasterEdition: web3.PublicKey
+    ): Promise<void> => {
+    const tx = new web3.Transaction().add(createVerifyCollectionInstruction({
+        metadata: metadata,
+        collectionAuthority: collectionAuthority.publicKey,
+        payer: collectionAuthority.publicKey,
+        collectionMint: collectionMint,
+        collection: collectionMetadata,
+        collectionMasterEditionAccount: collectionMasterEdition,
+    }))
+
+    await web3.sendAndConfirmTransaction(connection, tx, [collectionAuthority])
+}
+
+/**
+ * Create a programmable nft without a rule set and mint it to the recipient
+ * @param connection
+ * @param creator
+ * @param recipient
+ * @returns mint, metadata, master edition, recipient token account and token record
+ */
+export const createProgrammableNFT = async (
+    connection: web3.Connection,
+    creator: web3.Keypair,
+    recipient: web3.PublicKey
+    ): Promise<[web3.PublicKey, web3.PublicKey, web3.PublicKey, web3.PublicKey, web3.PublicKey]> => {
+    await safeAirdrop(creator.publicKey, connection)
+
+    const mint = web3.Keypair.generate()
+    const [metadataId, metadataBump] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer()],
+        METADATA_PROGRAM_ID
+    )
+    const [masterEditionId, masterEditionBump] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer(), Buffer.from(masterEditionSeed)],
+        METADATA_PROGRAM_ID
+    )
+    const tokenAccount = await getAssociatedTokenAddress(mint.publicKey, recipient)
+    const [tokenRecordId, tokenRecordBump] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer(), Buffer.from(tokenRecordSeed), tokenAccount.toBuffer()],
+        METADATA_PROGRAM_ID
+    )
+
+    const createIx = createCreateInstruction(
+        {
+            metadata: metadataId,
+            masterEdition: masterEditionId,
+            mint: mint.publicKey,
+            authority: creator.publicKey,
+            payer: creator.publicKey,
+            updateAuthority: creator.publicKey,
+            sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            splTokenProgram: TOKEN_PROGRAM_ID
+        },
+        {
+            createArgs: {
+                __kind: "V1",
+                assetData: {
+                    name: "test",
+                    symbol: "TST",
+                    uri: "http://test/",
+                    sellerFeeBasisPoints: 10,
+                    creators: [{ address: creator.publicKey, share: 100, verified: true }],
+                    primarySaleHappened: false,
+                    isMutable: true,
+                    tokenStandard: TokenStandard.ProgrammableNonFungible,
+                    collection: null,
+                    uses: null,
+                    collectionDetails: null,
+                    ruleSet: null
+                },
+                decimals: 0,
+                printSupply: { __kind: "Zero" }
+            }
+        }
+    )
+    // the metadata program creates the mint, so it has to sign
+    for (const key of createIx.keys) {
+        if (key.pubkey.equals(mint.publicKey)) {
+            key.isSigner = true
+            key.isWritable = true
+        }
+    }
+
+    const mintIx = createMintInstruction(
+        {
+            token: tokenAccount,
+            tokenOwner: recipient,
+            metadata: metadataId,
+            masterEdition: masterEditionId,
+            tokenRecord: tokenRecordId,
+            mint: mint.publicKey,
+            authority: creator.publicKey,
+            payer: creator.publicKey,
+            sysvarInstructions: web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            splTokenProgram: TOKEN_PROGRAM_ID,
+            splAtaProgram: ASSOCIATED_TOKEN_PROGRAM_ID
+        },
+        {
+            mintArgs: {
+                __kind: "V1",
+                amount: 1,
+                authorizationData: null
+            }
+        }
+    )
+
+    const tx = new web3.Transaction().add(
+        web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        createIx,
+        mintIx
+    )
+    const signature = await connection.sendTransaction(tx, [creator, mint])
+    await connection.confirmTransaction(signature, "confirmed")
+
+    return [mint.publicKey, metadataId, masterEditionId, tokenAccount, tokenRecordId]
+}
+
 export function delay(ms: number) {
     return new Promise( resolve => setTimeout(resolve, ms) );
 }
@@ -175,4 +316,49 @@ export async function safeAirdrop(address: web3.PublicKey, connection: web3.Conn
         )
         await connection.confirmTransaction(signature)
     }
-}
\ No newline at end of file
+}
+
+const merkleLeaf = (mint: web3.PublicKey): Buffer => Buffer.from(keccak_256(mint.toBuffer()))
+
+const merkleHashPair = (a: Buffer, b: Buffer): Buffer =>
+    Buffer.from(Buffer.compare(a, b) <= 0 ? keccak_256(Buffer.concat([a, b])) : keccak_256(Buffer.concat([b, a])))
+
+/**
+ * Builds the mint allowlist tree the same way as crates/breadhead-merkle.
+ * @returns the root to store on the pool and a proof lookup for init_entry
+ */
+export const buildMerkleTree = (mints: web3.PublicKey[]) => {
+    const leaves = mints.map(merkleLeaf)
+        .sort(Buffer.compare)
+        .filter((leaf, i, sorted) => i == 0 || !leaf.equals(sorted[i - 1]))
+
+    const layers = [leaves]
+    while (layers[layers.length - 1].length > 1) {
+        const layer = layers[layers.length - 1]
+        const next: Buffer[] = []
+        for (let i = 0; i < layer.length; i += 2) {
+            // an unpaired node is carried up to the next layer unchanged
+            next.push(i + 1 < layer.length ? merkleHashPair(layer[i], layer[i + 1]) : layer[i])
+        }
+        layers.push(next)
+    }
+
+    const root: number[] = Array.from(layers[layers.length - 1][0] ?? Buffer.alloc(32))
+    const proof = (mint: web3.PublicKey): number[][] | null => {
+        let index = leaves.findIndex((leaf) => leaf.equals(merkleLeaf(mint)))
+        if (index < 0) {
+            return null
+        }
+        const nodes: number[][] = []
+        for (const layer of layers.slice(0, -1)) {
+            const sibling = index ^ 1
+            if (sibling < layer.length) {
+                nodes.push(Array.from(layer[sibling]))
+            }
+            index = Math.floor(index / 2)
+        }
+        return nodes
+    }
+
+    return { root, proof }
+}
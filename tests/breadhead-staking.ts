@@ -1,12 +1,12 @@
 import * as anchor from "@project-serum/anchor"
 import { Program } from "@project-serum/anchor"
-import { PublicKey, SystemProgram, Keypair } from '@solana/web3.js'
+import { PublicKey, SystemProgram, Keypair, BPF_LOADER_UPGRADEABLE_PROGRAM_ID, SYSVAR_RENT_PUBKEY, SYSVAR_INSTRUCTIONS_PUBKEY } from '@solana/web3.js'
 import { BreadheadStaking } from "../target/types/breadhead_staking"
-import { IDENTIFIER_SEED, STAKE_POOL_SEED, STAKE_ENTRY_SEED } from '../src/stakePool/const'
-import { createNFTMint, createMasterEditionTxs, delay, safeAirdrop } from '../src/stakePool/utils'
-import { getAssociatedTokenAddress, getAccount } from '@solana/spl-token'
+import { IDENTIFIER_SEED, STAKE_POOL_SEED, STAKE_ENTRY_SEED, STAKE_AUTHORIZATION_SEED, PROGRAM_CONFIG_SEED, ACHIEVEMENT_TIER_SEED, BADGE_RECORD_SEED, BADGE_MINT_SEED, REWARD_DISTRIBUTOR_SEED, POOL_STATS_SEED, STAKE_MINT_SEED, STAKE_ESCROW_SEED, STAKER_PROFILE_SEED, masterEditionSeed, metadataSeed, tokenRecordSeed } from '../src/stakePool/const'
+import { buildMerkleTree, createNFTMint, createMasterEditionTxs, createProgrammableNFT, delay, safeAirdrop, verifyCollection } from '../src/stakePool/utils'
+import { getAssociatedTokenAddress, getAccount, ASSOCIATED_TOKEN_PROGRAM_ID, createMint, createAssociatedTokenAccount, mintTo, getMint } from '@solana/spl-token'
 import { TOKEN_PROGRAM_ID } from "@project-serum/anchor/dist/cjs/utils/token"
-import { PrimarySaleCanOnlyBeFlippedToTrueError, PROGRAM_ID as METADATA_PROGRAM_ID } from '@metaplex-foundation/mpl-token-metadata'
+import { PrimarySaleCanOnlyBeFlippedToTrueError, TokenRecord, TokenState, PROGRAM_ID as METADATA_PROGRAM_ID } from '@metaplex-foundation/mpl-token-metadata'
 import { BN } from "bn.js"
 import { assert } from "chai"
 import { token } from "@project-serum/anchor/dist/cjs/utils"
@@ -24,8 +24,23 @@ describe("breadhead-staking", async() => {
   let stakePool: PublicKey = null
   let originalMint: PublicKey = null
   let metadataInfo: [PublicKey, PublicKey] = null
+  let programConfig: PublicKey = null
+  let achievementTiers: PublicKey[] = []
+  let poolStats: PublicKey = null
+  let stakerProfile: PublicKey = null
+  let stakeEscrow: PublicKey = null
+  let tokenRecord: PublicKey = null
 
   const nftAuthority = Keypair.generate()
+  const secondAdmin = Keypair.generate()
+
+  // parse the program events emitted by a confirmed transaction
+  const getEvents = async (sig: string) => {
+    await connection.confirmTransaction(sig, "confirmed")
+    const txInfo = await connection.getTransaction(sig, { commitment: "confirmed" })
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl))
+    return Array.from(parser.parseLogs(txInfo.meta.logMessages))
+  }
 
   it("Create nft", async () => {
       await safeAirdrop(chiefBreadHead.publicKey, connection)
@@ -43,6 +58,101 @@ describe("breadhead-staking", async() => {
         nftAuthority,
         provider.connection
       )
+
+      // only used by programmable nfts
+      const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
+      const [tokenRecordId, tokenRecordBump] = await PublicKey.findProgramAddress(
+        [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), originalMint.toBuffer(), Buffer.from(tokenRecordSeed), userAta.toBuffer()],
+        METADATA_PROGRAM_ID
+      )
+      tokenRecord = tokenRecordId
+  })
+
+  it("Initialize program config", async () => {
+    const [programConfigId, programConfigBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(PROGRAM_CONFIG_SEED)],
+      program.programId
+    )
+    programConfig = programConfigId
+
+    const [programData, programDataBump] = await PublicKey.findProgramAddress(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    )
+
+    // localnet deploys with the provider wallet as upgrade authority
+    const tx = await program.methods.initProgramConfig([chiefBreadHead.publicKey, secondAdmin.publicKey])
+    .accounts({
+      programConfig: programConfig,
+      program: program.programId,
+      programData: programData,
+      authority: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const configAcct = await program.account.programConfig.fetch(programConfig, "confirmed")
+    assert(configAcct.admins[0].toBase58() == chiefBreadHead.publicKey.toBase58(), 'admin does not match')
+  })
+
+  it('Transfer and remove program config admins', async () => {
+    const adminKeys = async () => (await program.account.programConfig.fetch(programConfig, "confirmed")).admins.map((key) => key.toBase58()).sort()
+    const transferAdmin = async (from: Keypair, to: Keypair) => {
+      await program.methods.proposeAdmin(to.publicKey)
+      .accounts({ programConfig: programConfig, admin: from.publicKey })
+      .signers([from])
+      .rpc()
+      const tx = await program.methods.acceptAdmin()
+      .accounts({ programConfig: programConfig, pendingAdmin: to.publicKey })
+      .signers([to])
+      .rpc()
+      await connection.confirmTransaction(tx, "confirmed")
+    }
+
+    // only the proposed wallet can accept
+    const newAdmin = Keypair.generate()
+    const attacker = Keypair.generate()
+    await program.methods.proposeAdmin(newAdmin.publicKey)
+    .accounts({ programConfig: programConfig, admin: chiefBreadHead.publicKey })
+    .signers([chiefBreadHead])
+    .rpc()
+    let acceptError = null
+    try {
+      await program.methods.acceptAdmin()
+      .accounts({ programConfig: programConfig, pendingAdmin: attacker.publicKey })
+      .signers([attacker])
+      .rpc()
+    } catch (e) {
+      acceptError = e
+    }
+    assert(acceptError != null && acceptError.error.errorCode.code == 'InvalidPendingAdmin', 'another wallet accepted the admin transfer')
+
+    // accepting replaces the proposer instead of adding a seat
+    await transferAdmin(chiefBreadHead, newAdmin)
+    assert.deepEqual(await adminKeys(), [secondAdmin.publicKey.toBase58(), newAdmin.publicKey.toBase58()].sort(), 'proposer was not replaced')
+    await transferAdmin(newAdmin, chiefBreadHead)
+    assert.deepEqual(await adminKeys(), [secondAdmin.publicKey.toBase58(), chiefBreadHead.publicKey.toBase58()].sort(), 'admin was not transferred back')
+
+    // admins can step down but the last one stays
+    const removeTx = await program.methods.removeAdmin()
+    .accounts({ programConfig: programConfig, admin: secondAdmin.publicKey })
+    .signers([secondAdmin])
+    .rpc()
+    await connection.confirmTransaction(removeTx, "confirmed")
+    assert.deepEqual(await adminKeys(), [chiefBreadHead.publicKey.toBase58()], 'admin was not removed')
+
+    let removeError = null
+    try {
+      await program.methods.removeAdmin()
+      .accounts({ programConfig: programConfig, admin: chiefBreadHead.publicKey })
+      .signers([chiefBreadHead])
+      .rpc()
+    } catch (e) {
+      removeError = e
+    }
+    assert(removeError != null && removeError.error.errorCode.code == 'CannotRemoveLastAdmin', 'the last admin was removed')
   })
 
   it("Initialize stake pool", async () => {
@@ -52,23 +162,143 @@ describe("breadhead-staking", async() => {
     )
     stakePool = stakePoolId
 
+    const [poolStatsId, poolStatsBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(POOL_STATS_SEED), stakePool.toBuffer()],
+      program.programId
+    )
+    poolStats = poolStatsId
+
+    const [stakerProfileId, stakerProfileBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKER_PROFILE_SEED), stakePool.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    )
+    stakerProfile = stakerProfileId
+
     const tx = await program.methods.initPool({
       requiresCollections: [],
       requiresAuthorization: false,
+      authorizationMerkleRoot: null,
       authority: chiefBreadHead.publicKey,
       resetOnStake: false,
       cooldownSeconds: null,
       minStakeSeconds: null,
       endDate: null,
+      restLevels: [new BN(1), new BN(2), new BN(3), new BN(4), new BN(5)],
+      progressMode: 0,
+      progressDecayLevels: 0,
+      emergencyForfeitBps: 0,
+      emergencyLevelDrop: 0,
+      stakeMode: 0,
     })
     .accounts({
       stakePool: stakePool,
+      poolStats: poolStats,
       originalMint: originalMint,
+      programConfig: programConfig,
+      authority: chiefBreadHead.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+  })
+
+  it("Update stake pool", async () => {
+    const tx = await program.methods.updatePool({
+      requiresCollections: [],
+      requiresAuthorization: false,
+      authorizationMerkleRoot: null,
+      resetOnStake: false,
+      cooldownSeconds: null,
+      minStakeSeconds: null,
+      endDate: null,
+      restLevels: [new BN(1), new BN(2), new BN(3), new BN(4), new BN(5)],
+      progressMode: 0,
+      progressDecayLevels: 0,
+      emergencyForfeitBps: 5000,
+      emergencyLevelDrop: 1,
+    })
+    .accounts({
+      stakePool: stakePool,
+      authority: chiefBreadHead.publicKey,
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const poolAcct = await program.account.stakePool.fetch(stakePool, "confirmed")
+    assert(poolAcct.authority.toBase58() == chiefBreadHead.publicKey.toBase58(), 'pool authority does not match')
+    assert(poolAcct.endDate == null, 'end date was not updated')
+    assert(poolAcct.restLevels.length == 5, 'rest levels were not updated')
+
+    const updated = (await getEvents(tx)).find((event) => event.name == "PoolUpdated")
+    assert(updated != null, 'pool updated event was not emitted')
+    assert(updated.data.pool.toBase58() == stakePool.toBase58(), 'pool updated event pool does not match')
+    assert(updated.data.oldEmergencyForfeitBps == 0 && updated.data.newEmergencyForfeitBps == 5000, 'pool updated event penalty does not match')
+  })
+
+  it("Create achievement tiers", async () => {
+    const tiers = [
+      { threshold: 0, label: "DoughBoy", uri: "http://test/doughboy.json" },
+      { threshold: 1, label: "SixtyNineBadge", uri: "http://test/sixtynine.json" },
+    ]
+
+    for (let i = 0; i < tiers.length; i++) {
+      const [achievementTier, tierBump] = await PublicKey.findProgramAddress(
+        [Buffer.from(ACHIEVEMENT_TIER_SEED), stakePool.toBuffer(), Buffer.from([i])],
+        program.programId
+      )
+      achievementTiers.push(achievementTier)
+
+      const tx = await program.methods.initAchievementTier(tiers[i])
+      .accounts({
+        achievementTier: achievementTier,
+        stakePool: stakePool,
+        authority: chiefBreadHead.publicKey,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([chiefBreadHead])
+      .rpc()
+
+      await connection.confirmTransaction(tx)
+    }
+
+    const poolAcct = await program.account.stakePool.fetch(stakePool, "confirmed")
+    assert(poolAcct.achievementTierCount == tiers.length, 'achievement tier count does not match')
+  })
+
+  it('Authorize and deauthorize mint', async () => {
+    const [stakeAuthorizationRecord, recordBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_AUTHORIZATION_SEED), stakePool.toBuffer(), originalMint.toBuffer()],
+      program.programId
+    )
+
+    await program.methods.authorizeMint(originalMint)
+    .accounts({
+      stakeAuthorizationRecord: stakeAuthorizationRecord,
+      stakePool: stakePool,
       authority: chiefBreadHead.publicKey,
       systemProgram: SystemProgram.programId
     })
     .signers([chiefBreadHead])
     .rpc()
+
+    const recordAcct = await program.account.stakeAuthorizationRecord.fetch(stakeAuthorizationRecord, "confirmed")
+    assert(recordAcct.mint.toBase58() == originalMint.toBase58(), 'authorized mint does not match')
+
+    const tx = await program.methods.deauthorizeMint()
+    .accounts({
+      stakeAuthorizationRecord: stakeAuthorizationRecord,
+      stakePool: stakePool,
+      authority: chiefBreadHead.publicKey,
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const recordInfo = await connection.getAccountInfo(stakeAuthorizationRecord, "confirmed")
+    assert(recordInfo == null, 'stake authorization record was not closed')
   })
 
   it('Create stake entry', async () => {
@@ -77,7 +307,14 @@ describe("breadhead-staking", async() => {
       program.programId
     )
 
-    const tx = await program.methods.initEntry(provider.wallet.publicKey)
+    // only used by escrow mode pools
+    const [stakeEscrowId, stakeEscrowBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ESCROW_SEED), stakeEntry.toBuffer()],
+      program.programId
+    )
+    stakeEscrow = stakeEscrowId
+
+    const tx = await program.methods.initEntry(provider.wallet.publicKey, null)
     .accounts({
       stakeEntry: stakeEntry,
       stakePool: stakePool,
@@ -103,7 +340,7 @@ describe("breadhead-staking", async() => {
     )
 
     const [stakeState, stateBump] = await PublicKey.findProgramAddress(
-      [provider.wallet.publicKey.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
       program.programId
     )
 
@@ -113,14 +350,23 @@ describe("breadhead-staking", async() => {
     .accounts({
       stakeEntry: stakeEntry,
       stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
       programAuthority: programAuthority,
       originalMint: originalMint,
       masterEdition: metadataInfo[1],
+      originalMintMetadata: metadataInfo[0],
+      tokenRecord: tokenRecord,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
       user: provider.wallet.publicKey,
       userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: stakeEscrow,
       stakeState: stakeState,
       tokenProgram: TOKEN_PROGRAM_ID,
-      metadataProgram: METADATA_PROGRAM_ID
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
     })
     .rpc()
 
@@ -136,6 +382,17 @@ describe("breadhead-staking", async() => {
 
     const stakeEntryAcct = await program.account.stakeEntry.fetch(stakeEntry)
     console.log("Stake entry amt: ", stakeEntryAcct.amount.toString())
+
+    const statsAcct = await program.account.poolStats.fetch(poolStats, "confirmed")
+    assert(statsAcct.uniqueStakers.toNumber() == 1, 'unique stakers does not match')
+    assert(statsAcct.peakStaked.toNumber() == 1, 'peak staked does not match')
+    assert(statsAcct.levelHistogram[0].toNumber() == 1, 'level histogram does not match')
+
+    const staked = (await getEvents(tx)).find((event) => event.name == "Staked")
+    assert(staked != null, 'staked event was not emitted')
+    assert(staked.data.mint.toBase58() == originalMint.toBase58(), 'staked event mint does not match')
+    assert(staked.data.user.toBase58() == provider.wallet.publicKey.toBase58(), 'staked event user does not match')
+    assert(staked.data.amount.toNumber() == 1, 'staked event amount does not match')
   })
 
   it('Calculate reward level', async () => {
@@ -147,19 +404,23 @@ describe("breadhead-staking", async() => {
     )
 
     const [stakeState, stateBump] = await PublicKey.findProgramAddress(
-      [provider.wallet.publicKey.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
       program.programId
     )
     const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
 
     const tx = await program.methods.calculateReward()
     .accounts({
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
       stakeEntry: stakeEntry,
       originalMint: originalMint,
       user: provider.wallet.publicKey,
       userOriginalMintTokenAccount: userAta,
       stakeState: stakeState,
     })
+    .remainingAccounts(achievementTiers.map((tier) => ({ pubkey: tier, isSigner: false, isWritable: false })))
     .rpc()
 
     await connection.confirmTransaction(tx)
@@ -167,6 +428,165 @@ describe("breadhead-staking", async() => {
     const userState = await program.account.stakeState.fetch(stakeState, "confirmed")
     console.log("Resting level: ", userState.restingLevel.toString())
     console.log("Achievement level: ", userState.achievmentLevel)
+
+    // the stake state starts at level 0, so the first calculation changes it
+    const levelChanged = (await getEvents(tx)).find((event) => event.name == "RestingLevelChanged")
+    assert(levelChanged != null, 'resting level changed event was not emitted')
+    assert(levelChanged.data.oldRestingLevel == 0, 'resting level changed event old level does not match')
+    assert(levelChanged.data.newRestingLevel == userState.restingLevel, 'resting level changed event level does not match')
+    assert(levelChanged.data.amount.toNumber() == 1, 'resting level changed event amount does not match')
+    assert(levelChanged.data.totalStakeSeconds.gtn(0), 'resting level changed event stake seconds were not accrued')
+  })
+
+  it('Refresh resting level as keeper', async () => {
+    await delay(1000)
+
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), originalMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+
+    const stateBefore = await program.account.stakeState.fetch(stakeState, "confirmed")
+
+    // keeper pays the fee, the staker does not sign
+    const tx = await program.methods.refreshRestingLevel()
+    .accounts({
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      stakeEntry: stakeEntry,
+      stakeState: stakeState,
+    })
+    .remainingAccounts(achievementTiers.map((tier) => ({ pubkey: tier, isSigner: false, isWritable: false })))
+    .transaction()
+
+    const sig = await connection.sendTransaction(tx, [chiefBreadHead])
+    await connection.confirmTransaction(sig)
+
+    const userState = await program.account.stakeState.fetch(stakeState, "confirmed")
+    assert(userState.restingLevel > 0, 'resting level was not refreshed')
+    assert(userState.restingLevel >= stateBefore.restingLevel, 'resting level went down while staked')
+    assert(userState.restingLevel <= 5, 'resting level is above the pool rest levels')
+  })
+
+  it('Claim achievement badge', async () => {
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+    const userState = await program.account.stakeState.fetch(stakeState, "confirmed")
+    const tier = userState.achievmentLevel
+
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+    const [badgeRecord, recordBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(BADGE_RECORD_SEED), stakePool.toBuffer(), Buffer.from([tier]), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    )
+    const [badgeMint, mintBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(BADGE_MINT_SEED), badgeRecord.toBuffer()],
+      program.programId
+    )
+    const [badgeMetadata, metadataBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), badgeMint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )
+    const [badgeMasterEdition, editionBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), badgeMint.toBuffer(), Buffer.from(masterEditionSeed)],
+      METADATA_PROGRAM_ID
+    )
+    const userBadgeAta = await getAssociatedTokenAddress(badgeMint, provider.wallet.publicKey)
+
+    const tx = await program.methods.claimBadge()
+    .accounts({
+      stakePool: stakePool,
+      stakeState: stakeState,
+      originalMint: originalMint,
+      achievementTier: achievementTiers[tier],
+      badgeRecord: badgeRecord,
+      badgeMint: badgeMint,
+      badgeMetadata: badgeMetadata,
+      badgeMasterEdition: badgeMasterEdition,
+      programAuthority: programAuthority,
+      user: provider.wallet.publicKey,
+      userBadgeTokenAccount: userBadgeAta,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY
+    })
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const badgeAccount = await getAccount(provider.connection, userBadgeAta)
+    assert(badgeAccount.amount == BigInt(1), 'badge was not minted')
+    assert(badgeAccount.isFrozen, 'badge token account is not frozen')
+  })
+
+  it('Claim rewards', async () => {
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), originalMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+    const [rewardDistributor, distributorBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(REWARD_DISTRIBUTOR_SEED), stakePool.toBuffer()],
+      program.programId
+    )
+
+    const rewardMint = await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 0)
+    const userRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, provider.wallet.publicKey)
+    const distributorRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, rewardDistributor, undefined, undefined, ASSOCIATED_TOKEN_PROGRAM_ID, true)
+
+    await program.methods.initRewardDistributor({
+      kind: 0,
+      rewardRate: new BN(1),
+      maxSupply: null,
+    })
+    .accounts({
+      rewardDistributor: rewardDistributor,
+      stakePool: stakePool,
+      rewardMint: rewardMint,
+      authority: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
+    const tx = await program.methods.claimRewards()
+    .accounts({
+      rewardDistributor: rewardDistributor,
+      rewardDistributorTokenAccount: distributorRewardAta,
+      rewardMint: rewardMint,
+      stakePool: stakePool,
+      stakeEntry: stakeEntry,
+      originalMint: originalMint,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: userAta,
+      userRewardMintTokenAccount: userRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    })
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const rewardAccount = await getAccount(provider.connection, userRewardAta)
+    assert(rewardAccount.amount > BigInt(0), 'no rewards were paid')
+
+    const claimed = (await getEvents(tx)).find((event) => event.name == "RewardsClaimed")
+    assert(claimed != null, 'rewards claimed event was not emitted')
+    assert(claimed.data.rewardMint.toBase58() == rewardMint.toBase58(), 'rewards claimed event mint does not match')
+    assert(BigInt(claimed.data.amount.toString()) == rewardAccount.amount, 'rewards claimed event amount does not match')
   })
 
   it('Unstake nft', async () => {
@@ -181,24 +601,33 @@ describe("breadhead-staking", async() => {
     )
 
     const [stakeState, stateBump] = await PublicKey.findProgramAddress(
-      [provider.wallet.publicKey.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
       program.programId
     )
 
     const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
 
-    const tx = await program.methods.unstake()
+    const tx = await program.methods.unstake(new BN(1))
     .accounts({
       stakeEntry: stakeEntry,
       stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
       programAuthority: programAuthority,
       originalMint: originalMint,
       masterEdition: metadataInfo[1],
+      originalMintMetadata: metadataInfo[0],
+      tokenRecord: tokenRecord,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
       user: provider.wallet.publicKey,
       userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: stakeEscrow,
       stakeState: stakeState,
       tokenProgram: TOKEN_PROGRAM_ID,
-      metadataProgram: METADATA_PROGRAM_ID
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
     })
     .rpc()
 
@@ -215,5 +644,1539 @@ describe("breadhead-staking", async() => {
 
     const stakeEntryAcct = await program.account.stakeEntry.fetch(stakeEntry)
     console.log("Stake entry amt: ", stakeEntryAcct.amount.toString())
+
+    const profileAcct = await program.account.stakerProfile.fetch(stakerProfile, "confirmed")
+    assert(profileAcct.stakedCount.toNumber() == 0, 'staked count does not match')
+    assert(profileAcct.lifetimeStakeSeconds.toNumber() > 0, 'lifetime stake seconds were not recorded')
+    assert(profileAcct.firstStakedAt.toNumber() > 0, 'first stake timestamp was not recorded')
+
+    const unstaked = (await getEvents(tx)).find((event) => event.name == "Unstaked")
+    assert(unstaked != null, 'unstaked event was not emitted')
+    assert(unstaked.data.mint.toBase58() == originalMint.toBase58(), 'unstaked event mint does not match')
+    assert(unstaked.data.amount.toNumber() == 1, 'unstaked event amount does not match')
+    assert(unstaked.data.totalStakeSeconds.eq(stakeEntryAcct.totalStakeSeconds), 'unstaked event stake seconds do not match')
+  })
+
+  it('Stake and unstake many', async () => {
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), originalMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+
+    const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
+
+    // one group of (stake_entry, original_mint, master_edition, token_account, stake_state) per nft
+    const groups = [
+      { pubkey: stakeEntry, isWritable: true, isSigner: false },
+      { pubkey: originalMint, isWritable: false, isSigner: false },
+      { pubkey: metadataInfo[1], isWritable: false, isSigner: false },
+      { pubkey: userAta, isWritable: true, isSigner: false },
+      { pubkey: stakeState, isWritable: true, isSigner: false },
+    ]
+
+    // lamports sent to the stake state address beforehand must not block creating it
+    const fundTx = new anchor.web3.Transaction().add(
+      SystemProgram.transfer({ fromPubkey: provider.wallet.publicKey, toPubkey: stakeState, lamports: 1000 })
+    )
+    await provider.sendAndConfirm(fundTx)
+
+    const stakeTx = await program.methods.stakeMany()
+    .accounts({
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      programAuthority: programAuthority,
+      user: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    })
+    .remainingAccounts(groups)
+    .rpc()
+
+    await connection.confirmTransaction(stakeTx)
+
+    let tokenAccount = await getAccount(provider.connection, userAta)
+    assert(tokenAccount.isFrozen, 'token account is not frozen')
+    let poolAcct = await program.account.stakePool.fetch(stakePool, "confirmed")
+    assert(poolAcct.totalStaked.toNumber() == 1, 'total staked does not match')
+
+    const unstakeTx = await program.methods.unstakeMany()
+    .accounts({
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      programAuthority: programAuthority,
+      user: provider.wallet.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID
+    })
+    .remainingAccounts(groups)
+    .rpc()
+
+    await connection.confirmTransaction(unstakeTx)
+
+    tokenAccount = await getAccount(provider.connection, userAta)
+    assert(!tokenAccount.isFrozen, 'token account is still frozen')
+    assert(tokenAccount.delegate == null, 'delegate does not match')
+    poolAcct = await program.account.stakePool.fetch(stakePool, "confirmed")
+    assert(poolAcct.totalStaked.toNumber() == 0, 'total staked does not match')
+    const stateInfo = await connection.getAccountInfo(stakeState, "confirmed")
+    assert(stateInfo == null, 'stake state was not closed')
+  })
+
+  it('Emergency unstake', async () => {
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), originalMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+
+    const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
+
+    const accounts = {
+      stakeEntry: stakeEntry,
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      programAuthority: programAuthority,
+      originalMint: originalMint,
+      masterEdition: metadataInfo[1],
+      originalMintMetadata: metadataInfo[0],
+      tokenRecord: tokenRecord,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: stakeEscrow,
+      stakeState: stakeState,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+    }
+
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx)
+
+    // reach at least the first resting level before the penalty
+    await delay(2500)
+    const refreshTx = await program.methods.refreshRestingLevel()
+    .accounts({
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      stakeEntry: stakeEntry,
+      stakeState: stakeState,
+    })
+    .remainingAccounts(achievementTiers.map((tier) => ({ pubkey: tier, isSigner: false, isWritable: false })))
+    .rpc()
+    await connection.confirmTransaction(refreshTx, "confirmed")
+
+    const entryBefore = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    const profileBefore = await program.account.stakerProfile.fetch(stakerProfile, "confirmed")
+    const stateBefore = await program.account.stakeState.fetch(stakeState, "confirmed")
+    assert(stateBefore.restingLevel > 0, 'resting level was not reached before emergency unstake')
+
+    const tx = await program.methods.emergencyUnstake()
+    .accounts(accounts)
+    .rpc()
+    const events = await getEvents(tx)
+
+    const tokenAccount = await getAccount(provider.connection, userAta)
+    assert(!tokenAccount.isFrozen, 'token account is still frozen')
+    assert(tokenAccount.delegate == null, 'delegate does not match')
+
+    // half of the stake seconds are forfeited
+    const entryAfter = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAfter.amount.toNumber() == 0, 'stake entry amount does not match')
+
+    const penalty = events.find((event) => event.name == "EmergencyUnstaked")
+    assert(penalty != null, 'emergency unstake event was not emitted')
+    const forfeited = penalty.data.forfeitedStakeSeconds as BN
+    const totalStakeSeconds = penalty.data.totalStakeSeconds as BN
+    const unforfeited = totalStakeSeconds.add(forfeited)
+    assert(forfeited.gt(new BN(0)), 'no stake seconds were forfeited')
+    assert(forfeited.eq(unforfeited.muln(5000).divn(10000)), 'forfeited stake seconds do not match')
+    assert(entryAfter.totalStakeSeconds.eq(totalStakeSeconds), 'stake entry seconds do not match')
+
+    // one resting level is dropped
+    assert(penalty.data.oldRestingLevel == stateBefore.restingLevel, 'old resting level does not match')
+    assert(penalty.data.newRestingLevel == stateBefore.restingLevel - 1, 'resting level was not dropped')
+
+    // the forfeited seconds are not kept in the staker lifetime
+    const profileAfter = await program.account.stakerProfile.fetch(stakerProfile, "confirmed")
+    const accrued = unforfeited.sub(entryBefore.totalStakeSeconds)
+    assert(profileAfter.lifetimeStakeSeconds.eq(profileBefore.lifetimeStakeSeconds.add(accrued).sub(forfeited)), 'lifetime stake seconds do not match')
+  })
+
+  it('Stake and unstake with receipt', async () => {
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), originalMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), originalMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+
+    const [stakeMint, stakeMintBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_MINT_SEED), stakeEntry.toBuffer()],
+      program.programId
+    )
+    const [stakeMintMetadata, stakeMintMetadataBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), stakeMint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )
+
+    const stakeMintAccounts = {
+      stakeEntry: stakeEntry,
+      stakePool: stakePool,
+      originalMint: originalMint,
+      originalMintMetadata: metadataInfo[0],
+      stakeMint: stakeMint,
+      stakeMintMetadata: stakeMintMetadata,
+      programAuthority: programAuthority,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY
+    }
+
+    // another wallet cannot attach a receipt mint to the entry
+    const attacker = Keypair.generate()
+    await safeAirdrop(attacker.publicKey, connection)
+    let stakeMintError = null
+    try {
+      await program.methods.initStakeMint()
+      .accounts({ ...stakeMintAccounts, payer: attacker.publicKey })
+      .signers([attacker])
+      .rpc()
+    } catch (e) {
+      stakeMintError = e
+    }
+    assert(stakeMintError != null && stakeMintError.error.errorCode.code == 'InvalidAuthority', 'another wallet attached a receipt mint')
+
+    const initTx = await program.methods.initStakeMint()
+    .accounts(stakeMintAccounts)
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(initTx)
+
+    const userAta = await getAssociatedTokenAddress(originalMint, provider.wallet.publicKey)
+    const userReceiptAta = await createAssociatedTokenAccount(connection, chiefBreadHead, stakeMint, provider.wallet.publicKey)
+
+    const accounts = {
+      stakeEntry: stakeEntry,
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      programAuthority: programAuthority,
+      originalMint: originalMint,
+      masterEdition: metadataInfo[1],
+      originalMintMetadata: metadataInfo[0],
+      tokenRecord: tokenRecord,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: stakeEscrow,
+      stakeState: stakeState,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+    }
+    const receiptAccounts = [
+      { pubkey: stakeMint, isWritable: true, isSigner: false },
+      { pubkey: userReceiptAta, isWritable: true, isSigner: false },
+    ]
+
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .remainingAccounts(receiptAccounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx)
+
+    let receiptAccount = await getAccount(provider.connection, userReceiptAta)
+    assert(Number(receiptAccount.amount) == 1, 'receipt was not minted')
+
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .remainingAccounts(receiptAccounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx)
+
+    receiptAccount = await getAccount(provider.connection, userReceiptAta)
+    assert(Number(receiptAccount.amount) == 0, 'receipt was not burned')
+  })
+
+  // escrow mode pool with a stake entry and escrow for a fresh fungible mint held by the wallet
+  it('Stake and unstake programmable nft', async () => {
+    const [pnftMint, pnftMetadata, pnftMasterEdition, userAta, pnftTokenRecord] = await createProgrammableNFT(
+      connection,
+      nftAuthority,
+      provider.wallet.publicKey
+    )
+
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), pnftMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), stakePool.toBuffer(), pnftMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+    const [pnftEscrow, pnftEscrowBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ESCROW_SEED), stakeEntry.toBuffer()],
+      program.programId
+    )
+
+    await program.methods.initEntry(provider.wallet.publicKey, null)
+    .accounts({
+      stakeEntry: stakeEntry,
+      stakePool: stakePool,
+      originalMint: pnftMint,
+      originalMintMetadata: pnftMetadata,
+      payer: chiefBreadHead.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    // the pnft has no rule set, so the authorization rules are placeholders
+    const accounts = {
+      stakeEntry: stakeEntry,
+      stakePool: stakePool,
+      poolStats: poolStats,
+      stakerProfile: stakerProfile,
+      programAuthority: programAuthority,
+      originalMint: pnftMint,
+      masterEdition: pnftMasterEdition,
+      originalMintMetadata: pnftMetadata,
+      tokenRecord: pnftTokenRecord,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: pnftEscrow,
+      stakeState: stakeState,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+    }
+
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+    .rpc()
+    await connection.confirmTransaction(stakeTx, "confirmed")
+
+    let record = await TokenRecord.fromAccountAddress(connection, pnftTokenRecord, "confirmed")
+    assert(record.state == TokenState.Locked, 'token record is not locked')
+    assert(record.delegate.toBase58() == programAuthority.toBase58(), 'token record delegate does not match')
+    let tokenAccount = await getAccount(provider.connection, userAta, "confirmed")
+    assert(tokenAccount.amount == BigInt(1), 'pnft left the user token account')
+    const entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAcct.amount.toNumber() == 1, 'stake entry amount does not match')
+
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+
+    record = await TokenRecord.fromAccountAddress(connection, pnftTokenRecord, "confirmed")
+    assert(record.state == TokenState.Unlocked, 'token record is still locked')
+    assert(record.delegate == null, 'token record delegate was not revoked')
+    tokenAccount = await getAccount(provider.connection, userAta, "confirmed")
+    assert(tokenAccount.delegate == null, 'delegate was not revoked')
+    const unstakedEntry = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(unstakedEntry.amount.toNumber() == 0, 'stake entry amount does not match')
+  })
+
+  const setupFungiblePool = async ({ endDate = null, cooldownSeconds = null, resetOnStake = false, progressMode = 0, progressDecayLevels = 0 }: { endDate?: BN | null, cooldownSeconds?: number | null, resetOnStake?: boolean, progressMode?: number, progressDecayLevels?: number } = {}) => {
+    const breadMint = await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 9)
+    const userAta = await createAssociatedTokenAccount(connection, chiefBreadHead, breadMint, provider.wallet.publicKey)
+    await mintTo(connection, chiefBreadHead, breadMint, userAta, chiefBreadHead, 1000)
+
+    const [breadPool, breadPoolBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_POOL_SEED), breadMint.toBuffer()],
+      program.programId
+    )
+    const [breadPoolStats, breadPoolStatsBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(POOL_STATS_SEED), breadPool.toBuffer()],
+      program.programId
+    )
+    const [breadStakerProfile, breadStakerProfileBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKER_PROFILE_SEED), breadPool.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    )
+    // fungible entries are keyed per user
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), breadPool.toBytes(), breadMint.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    )
+    const [escrowTokenAccount, escrowBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ESCROW_SEED), stakeEntry.toBuffer()],
+      program.programId
+    )
+    const [breadMetadata, breadMetadataBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), breadMint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), breadPool.toBuffer(), breadMint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+
+    await program.methods.initPool({
+      requiresCollections: [],
+      requiresAuthorization: false,
+      authorizationMerkleRoot: null,
+      authority: chiefBreadHead.publicKey,
+      resetOnStake: resetOnStake,
+      cooldownSeconds: cooldownSeconds,
+      minStakeSeconds: null,
+      endDate: endDate,
+      restLevels: [new BN(1), new BN(2), new BN(3), new BN(4), new BN(5)],
+      progressMode: progressMode,
+      progressDecayLevels: progressDecayLevels,
+      emergencyForfeitBps: 0,
+      emergencyLevelDrop: 0,
+      stakeMode: 1,
+    })
+    .accounts({
+      stakePool: breadPool,
+      poolStats: breadPoolStats,
+      originalMint: breadMint,
+      programConfig: programConfig,
+      authority: chiefBreadHead.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    await program.methods.initEntry(provider.wallet.publicKey, null)
+    .accounts({
+      stakeEntry: stakeEntry,
+      stakePool: breadPool,
+      originalMint: breadMint,
+      originalMintMetadata: breadMetadata,
+      payer: chiefBreadHead.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    await program.methods.initStakeEscrow()
+    .accounts({
+      stakePool: breadPool,
+      stakeEntry: stakeEntry,
+      originalMint: breadMint,
+      escrowTokenAccount: escrowTokenAccount,
+      programAuthority: programAuthority,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    // fungible mints have no master edition or token record
+    const accounts = {
+      stakeEntry: stakeEntry,
+      stakePool: breadPool,
+      poolStats: breadPoolStats,
+      stakerProfile: breadStakerProfile,
+      programAuthority: programAuthority,
+      originalMint: breadMint,
+      masterEdition: breadMetadata,
+      originalMintMetadata: breadMetadata,
+      tokenRecord: METADATA_PROGRAM_ID,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: escrowTokenAccount,
+      stakeState: stakeState,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+    }
+
+    return { breadMint, breadPool, breadPoolStats, breadStakerProfile, stakeEntry, escrowTokenAccount, userAta, accounts }
+  }
+
+  const setupRewardDistributor = async (pool: PublicKey, kind: number) => {
+    const [rewardDistributor, distributorBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(REWARD_DISTRIBUTOR_SEED), pool.toBuffer()],
+      program.programId
+    )
+    const rewardMint = await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 0)
+    const distributorRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, rewardDistributor, undefined, undefined, ASSOCIATED_TOKEN_PROGRAM_ID, true)
+
+    await program.methods.initRewardDistributor({
+      kind: kind,
+      rewardRate: new BN(1),
+      maxSupply: null,
+    })
+    .accounts({
+      rewardDistributor: rewardDistributor,
+      stakePool: pool,
+      rewardMint: rewardMint,
+      authority: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    return { rewardDistributor, rewardMint, distributorRewardAta }
+  }
+
+  // pool keyed by a fresh mint, optionally limited to allowlisted mints
+  const setupAllowlistPool = async ({ requiresCollections = [], requiresAuthorization = false, authorizationMerkleRoot = null, stakeMode = 0 }: { requiresCollections?: PublicKey[], requiresAuthorization?: boolean, authorizationMerkleRoot?: number[] | null, stakeMode?: number } = {}) => {
+    const poolMint = await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 0)
+    const [pool, poolBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_POOL_SEED), poolMint.toBuffer()],
+      program.programId
+    )
+    const [poolStatsId, poolStatsBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(POOL_STATS_SEED), pool.toBuffer()],
+      program.programId
+    )
+
+    await program.methods.initPool({
+      requiresCollections: requiresCollections,
+      requiresAuthorization: requiresAuthorization,
+      authorizationMerkleRoot: authorizationMerkleRoot,
+      authority: chiefBreadHead.publicKey,
+      resetOnStake: false,
+      cooldownSeconds: null,
+      minStakeSeconds: null,
+      endDate: null,
+      restLevels: [new BN(1), new BN(2), new BN(3), new BN(4), new BN(5)],
+      progressMode: 0,
+      progressDecayLevels: 0,
+      emergencyForfeitBps: 0,
+      emergencyLevelDrop: 0,
+      stakeMode: stakeMode,
+    })
+    .accounts({
+      stakePool: pool,
+      poolStats: poolStatsId,
+      originalMint: poolMint,
+      programConfig: programConfig,
+      authority: chiefBreadHead.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    return pool
+  }
+
+  // returns the error code of a failed init entry, or null when the entry was created
+  const tryInitEntry = async (pool: PublicKey, mint: PublicKey, proof: number[][] | null) => {
+    const [entry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), pool.toBytes(), mint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+    const [metadata, metadataBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )
+
+    try {
+      const tx = await program.methods.initEntry(provider.wallet.publicKey, proof)
+      .accounts({
+        stakeEntry: entry,
+        stakePool: pool,
+        originalMint: mint,
+        originalMintMetadata: metadata,
+        payer: chiefBreadHead.publicKey,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([chiefBreadHead])
+      .rpc()
+      await connection.confirmTransaction(tx, "confirmed")
+    } catch (e) {
+      return e.error.errorCode.code
+    }
+
+    const entryAcct = await program.account.stakeEntry.fetch(entry, "confirmed")
+    assert(entryAcct.originalMint.toBase58() == mint.toBase58(), 'stake entry mint does not match')
+    return null
+  }
+
+  it('Stake, partially unstake and settle fungible tokens', async () => {
+    // short season so the entry can be settled
+    const { breadPool, breadPoolStats, breadStakerProfile, stakeEntry, escrowTokenAccount, userAta, accounts } = await setupFungiblePool({
+      endDate: new BN(Math.floor(Date.now() / 1000) + 10)
+    })
+
+    const stakeTx = await program.methods.stake(new BN(400))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx)
+
+    let escrowAccount = await getAccount(provider.connection, escrowTokenAccount)
+    assert(Number(escrowAccount.amount) == 400, 'escrow amount does not match')
+    let entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAcct.amount.toNumber() == 400, 'stake entry amount does not match')
+
+    const partialTx = await program.methods.unstake(new BN(150))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(partialTx)
+
+    entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAcct.amount.toNumber() == 250, 'stake entry amount does not match')
+    assert(entryAcct.lastStaker.toBase58() == provider.wallet.publicKey.toBase58(), 'entry is no longer staked')
+    const userAccount = await getAccount(provider.connection, userAta)
+    assert(Number(userAccount.amount) == 750, 'user amount does not match')
+
+    // anyone can settle once the season has ended
+    await delay(11000)
+    const settleAccounts = {
+      stakePool: breadPool,
+      poolStats: breadPoolStats,
+      stakeEntry: stakeEntry,
+      stakerProfile: breadStakerProfile
+    }
+    await program.methods.settleEntry()
+    .accounts(settleAccounts)
+    .rpc()
+    const settledEntry = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+
+    await delay(2000)
+    await program.methods.settleEntry()
+    .accounts(settleAccounts)
+    .rpc()
+    entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAcct.totalStakeSeconds.eq(settledEntry.totalStakeSeconds), 'stake seconds accrued after the end date')
+
+    const unstakeTx = await program.methods.unstake(new BN(250))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx)
+
+    escrowAccount = await getAccount(provider.connection, escrowTokenAccount)
+    assert(Number(escrowAccount.amount) == 0, 'escrow was not emptied')
+    entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAcct.amount.toNumber() == 0, 'stake entry amount does not match')
+    assert(entryAcct.totalStakeSeconds.eq(settledEntry.totalStakeSeconds), 'stake seconds accrued after the end date')
+  })
+
+  it('Partially unstake fungible tokens after cooldown', async () => {
+    const { stakeEntry, userAta, accounts } = await setupFungiblePool({ cooldownSeconds: 2 })
+    const stakeTx = await program.methods.stake(new BN(400))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx, "confirmed")
+    const stakedEntry = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+
+    // the first call only starts the cooldown
+    await delay(2000)
+    const cooldownTx = await program.methods.unstake(new BN(150))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(cooldownTx, "confirmed")
+    const cooldownEntry = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(cooldownEntry.cooldownStartSeconds != null, 'cooldown was not started')
+    assert(cooldownEntry.amount.toNumber() == 400, 'tokens were unstaked before the cooldown')
+
+    await delay(3000)
+    const unstakeTx = await program.methods.unstake(new BN(150))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+
+    // the withdrawn amount accrues until the cooldown start, the remaining amount until the unstake
+    const entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    const cooldownStart = cooldownEntry.cooldownStartSeconds as BN
+    const expectedStakeSeconds = cooldownStart.sub(stakedEntry.lastStakedAt).muln(400)
+      .add(entryAcct.lastStakedAt.sub(cooldownStart).muln(250))
+    assert(entryAcct.amount.toNumber() == 250, 'stake entry amount does not match')
+    assert(entryAcct.cooldownStartSeconds == null, 'cooldown was not cleared')
+    assert(entryAcct.totalStakeSeconds.eq(expectedStakeSeconds), 'stake seconds do not match')
+    const userAccount = await getAccount(provider.connection, userAta, "confirmed")
+    assert(Number(userAccount.amount) == 750, 'user amount does not match')
+  })
+
+  // stake until a few resting levels are reached, unstake and stake again under the given progress mode
+  const restakeWithProgressMode = async (progressMode: number, progressDecayLevels: number) => {
+    const { breadPool, breadPoolStats, breadStakerProfile, stakeEntry, accounts } = await setupFungiblePool({ progressMode, progressDecayLevels })
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx, "confirmed")
+
+    await delay(3500)
+    const refreshTx = await program.methods.refreshRestingLevel()
+    .accounts({
+      stakePool: breadPool,
+      poolStats: breadPoolStats,
+      stakerProfile: breadStakerProfile,
+      stakeEntry: stakeEntry,
+      stakeState: accounts.stakeState,
+    })
+    .rpc()
+    await connection.confirmTransaction(refreshTx, "confirmed")
+    const stakedState = await program.account.stakeState.fetch(accounts.stakeState, "confirmed")
+    assert(stakedState.restingLevel >= 3, 'resting level was not reached')
+
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+    const stateInfo = await connection.getAccountInfo(accounts.stakeState, "confirmed")
+    assert(stateInfo != null, 'stake state was closed')
+
+    const restakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(restakeTx, "confirmed")
+    const restakedState = await program.account.stakeState.fetch(accounts.stakeState, "confirmed")
+    const entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+
+    return { stakedState, restakedState, entryAcct }
+  }
+
+  it('Resume resting level on re-stake with preserve progress mode', async () => {
+    const { stakedState, restakedState, entryAcct } = await restakeWithProgressMode(1, 0)
+
+    // the rest levels of the pool are 1..5 seconds
+    assert(restakedState.restingLevel == stakedState.restingLevel, 'resting level was not preserved')
+    assert(restakedState.stakeStart.eq(entryAcct.lastStakedAt.subn(restakedState.restingLevel)), 'stake start was not backdated')
+  })
+
+  it('Decay resting level on re-stake with decay progress mode', async () => {
+    const { stakedState, restakedState, entryAcct } = await restakeWithProgressMode(2, 2)
+
+    assert(restakedState.restingLevel == stakedState.restingLevel - 2, 'resting level was not decayed')
+    assert(restakedState.achievmentLevel == null, 'achievement level was not cleared')
+    assert(restakedState.stakeStart.eq(entryAcct.lastStakedAt.subn(restakedState.restingLevel)), 'stake start was not backdated')
+  })
+
+  it('Reject claiming rewards from another wallet fungible entry', async () => {
+    const { breadMint, breadPool, stakeEntry, accounts } = await setupFungiblePool()
+    await program.methods.stake(new BN(400))
+    .accounts(accounts)
+    .rpc()
+    const { rewardDistributor, rewardMint, distributorRewardAta } = await setupRewardDistributor(breadPool, 0)
+
+    // a second wallet holding a single token points at the wallet's entry
+    const attacker = Keypair.generate()
+    await safeAirdrop(attacker.publicKey, connection)
+    const attackerAta = await createAssociatedTokenAccount(connection, chiefBreadHead, breadMint, attacker.publicKey)
+    await mintTo(connection, chiefBreadHead, breadMint, attackerAta, chiefBreadHead, 1)
+    const attackerRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, attacker.publicKey)
+
+    let claimError = null
+    try {
+      await program.methods.claimRewards()
+      .accounts({
+        rewardDistributor: rewardDistributor,
+        rewardDistributorTokenAccount: distributorRewardAta,
+        rewardMint: rewardMint,
+        stakePool: breadPool,
+        stakeEntry: stakeEntry,
+        originalMint: breadMint,
+        user: attacker.publicKey,
+        userOriginalMintTokenAccount: attackerAta,
+        userRewardMintTokenAccount: attackerRewardAta,
+        tokenProgram: TOKEN_PROGRAM_ID
+      })
+      .signers([attacker])
+      .rpc()
+    } catch (e) {
+      claimError = e
+    }
+    assert(claimError != null && claimError.error.errorCode.code == 'ConstraintSeeds', 'another wallet claimed the entry rewards')
+
+    const attackerRewardAccount = await getAccount(provider.connection, attackerRewardAta)
+    assert(Number(attackerRewardAccount.amount) == 0, 'rewards were paid to another wallet')
+  })
+
+  it('Claim rewards again after the entry stake seconds are reset', async () => {
+    const { breadPool, stakeEntry, accounts } = await setupFungiblePool({ resetOnStake: true })
+    await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    const { rewardDistributor, rewardMint, distributorRewardAta } = await setupRewardDistributor(breadPool, 0)
+
+    const userRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, provider.wallet.publicKey)
+    const claimAccounts = {
+      rewardDistributor: rewardDistributor,
+      rewardDistributorTokenAccount: distributorRewardAta,
+      rewardMint: rewardMint,
+      stakePool: breadPool,
+      stakeEntry: stakeEntry,
+      originalMint: accounts.originalMint,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: accounts.userOriginalMintTokenAccount,
+      userRewardMintTokenAccount: userRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }
+
+    await delay(3000)
+    const claimTx = await program.methods.claimRewards()
+    .accounts(claimAccounts)
+    .rpc()
+    await connection.confirmTransaction(claimTx, "confirmed")
+    const firstPayout = (await getAccount(provider.connection, userRewardAta, "confirmed")).amount
+    assert(firstPayout > BigInt(0), 'no rewards were paid')
+
+    // restaking resets the entry stake seconds together with the paid seconds
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+    const restakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(restakeTx, "confirmed")
+    let entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(entryAcct.totalStakeSeconds.toNumber() == 0, 'stake seconds were not reset')
+    assert(entryAcct.rewardSecondsClaimed.toNumber() == 0, 'paid stake seconds were not reset')
+
+    await delay(2000)
+    const reclaimTx = await program.methods.claimRewards()
+    .accounts(claimAccounts)
+    .rpc()
+    await connection.confirmTransaction(reclaimTx, "confirmed")
+    const secondPayout = (await getAccount(provider.connection, userRewardAta, "confirmed")).amount - firstPayout
+    assert(secondPayout > BigInt(0), 'rewards after the reset were not paid')
+    entryAcct = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    assert(BigInt(entryAcct.rewardSecondsClaimed.toString()) == secondPayout, 'paid stake seconds do not match')
+  })
+
+  it('Close mint reward distributor', async () => {
+    const { breadPool, stakeEntry } = await setupFungiblePool()
+    const { rewardDistributor, rewardMint, distributorRewardAta } = await setupRewardDistributor(breadPool, 0)
+    const authorityRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, chiefBreadHead.publicKey)
+    const closeAccounts = {
+      rewardDistributor: rewardDistributor,
+      rewardDistributorTokenAccount: distributorRewardAta,
+      rewardMint: rewardMint,
+      stakePool: breadPool,
+      authority: chiefBreadHead.publicKey,
+      authorityRewardMintTokenAccount: authorityRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    }
+
+    // only the distributor authority can close it
+    const attacker = Keypair.generate()
+    await safeAirdrop(attacker.publicKey, connection)
+    const attackerRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, attacker.publicKey)
+    let closeError = null
+    try {
+      await program.methods.closeRewardDistributor()
+      .accounts({ ...closeAccounts, authority: attacker.publicKey, authorityRewardMintTokenAccount: attackerRewardAta })
+      .signers([attacker])
+      .rpc()
+    } catch (e) {
+      closeError = e
+    }
+    assert(closeError != null && closeError.error.errorCode.code == 'InvalidAuthority', 'another wallet closed the reward distributor')
+
+    const tx = await program.methods.closeRewardDistributor()
+    .accounts(closeAccounts)
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(tx, "confirmed")
+
+    const mintAcct = await getMint(provider.connection, rewardMint, "confirmed")
+    assert(mintAcct.mintAuthority.toBase58() == chiefBreadHead.publicKey.toBase58(), 'mint authority was not returned')
+    assert(await connection.getAccountInfo(rewardDistributor, "confirmed") == null, 'reward distributor was not closed')
+    assert(await connection.getAccountInfo(distributorRewardAta, "confirmed") == null, 'distributor token account was not closed')
+  })
+
+  it('Close treasury reward distributor', async () => {
+    const { breadPool, stakeEntry } = await setupFungiblePool()
+    const { rewardDistributor, rewardMint, distributorRewardAta } = await setupRewardDistributor(breadPool, 1)
+    await mintTo(connection, chiefBreadHead, rewardMint, distributorRewardAta, chiefBreadHead, 100)
+    const authorityRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, chiefBreadHead.publicKey)
+
+    const tx = await program.methods.closeRewardDistributor()
+    .accounts({
+      rewardDistributor: rewardDistributor,
+      rewardDistributorTokenAccount: distributorRewardAta,
+      rewardMint: rewardMint,
+      stakePool: breadPool,
+      authority: chiefBreadHead.publicKey,
+      authorityRewardMintTokenAccount: authorityRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(tx, "confirmed")
+
+    // the unpaid treasury is withdrawn to the authority
+    const authorityRewardAccount = await getAccount(provider.connection, authorityRewardAta, "confirmed")
+    assert(Number(authorityRewardAccount.amount) == 100, 'treasury was not withdrawn')
+    assert(await connection.getAccountInfo(rewardDistributor, "confirmed") == null, 'reward distributor was not closed')
+    assert(await connection.getAccountInfo(distributorRewardAta, "confirmed") == null, 'distributor token account was not closed')
+  })
+
+  it('Create stake entries with an authorization merkle proof', async () => {
+    const allowedMints = []
+    for (let i = 0; i < 3; i++) {
+      allowedMints.push(await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 0))
+    }
+    const otherMint = await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 0)
+    const tree = buildMerkleTree(allowedMints)
+    const merklePool = await setupAllowlistPool({ requiresAuthorization: true, authorizationMerkleRoot: tree.root })
+
+    // three leaves cover both a paired leaf and the unpaired one carried up
+    for (const mint of allowedMints) {
+      assert(await tryInitEntry(merklePool, mint, tree.proof(mint)) == null, 'valid proof was rejected')
+    }
+
+    // a proof for another mint does not prove a mint outside the tree
+    assert(await tryInitEntry(merklePool, otherMint, tree.proof(allowedMints[1])) == 'MintNotAllowedInPool', 'invalid proof was accepted')
+    assert(await tryInitEntry(merklePool, otherMint, null) == 'MintNotAllowedInPool', 'mint outside the tree was accepted without a proof')
+  })
+
+  it('Create stake entries for a required collection', async () => {
+    const collectionMint = await createNFTMint(connection, nftAuthority, nftAuthority.publicKey)
+    const [collectionMetadata, collectionMasterEdition] = await createMasterEditionTxs(collectionMint, nftAuthority, connection)
+    const collectionPool = await setupAllowlistPool({ requiresCollections: [collectionMint] })
+
+    // both mints name the collection but only one is verified by its authority
+    const verifiedMint = await createNFTMint(connection, nftAuthority, provider.wallet.publicKey)
+    const [verifiedMetadata] = await createMasterEditionTxs(verifiedMint, nftAuthority, connection, collectionMint)
+    await verifyCollection(connection, nftAuthority, verifiedMetadata, collectionMint, collectionMetadata, collectionMasterEdition)
+    const unverifiedMint = await createNFTMint(connection, nftAuthority, provider.wallet.publicKey)
+    await createMasterEditionTxs(unverifiedMint, nftAuthority, connection, collectionMint)
+
+    assert(await tryInitEntry(collectionPool, verifiedMint, null) == null, 'verified collection mint was rejected')
+    assert(await tryInitEntry(collectionPool, unverifiedMint, null) == 'MintNotAllowedInPool', 'unverified collection mint was accepted')
+  })
+
+  it('Reject pool updates that lock or end an active stake', async () => {
+    const { breadPool, accounts } = await setupFungiblePool({ cooldownSeconds: 10 })
+    await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+
+    const tryUpdatePool = async ({ cooldownSeconds = 10, minStakeSeconds = null, endDate = null }: { cooldownSeconds?: number | null, minStakeSeconds?: number | null, endDate?: BN | null }) => {
+      try {
+        await program.methods.updatePool({
+          requiresCollections: [],
+          requiresAuthorization: false,
+          authorizationMerkleRoot: null,
+          resetOnStake: false,
+          cooldownSeconds: cooldownSeconds,
+          minStakeSeconds: minStakeSeconds,
+          endDate: endDate,
+          restLevels: [new BN(1), new BN(2), new BN(3), new BN(4), new BN(5)],
+          progressMode: 0,
+          progressDecayLevels: 0,
+          emergencyForfeitBps: 0,
+          emergencyLevelDrop: 0,
+        })
+        .accounts({
+          stakePool: breadPool,
+          authority: chiefBreadHead.publicKey,
+        })
+        .signers([chiefBreadHead])
+        .rpc()
+      } catch (e) {
+        return e.error.errorCode.code
+      }
+      return null
+    }
+
+    assert(await tryUpdatePool({ cooldownSeconds: 1000 }) == 'CannotIncreaseLockWhileStaked', 'cooldown was increased while staked')
+    assert(await tryUpdatePool({ minStakeSeconds: 1000 }) == 'CannotIncreaseLockWhileStaked', 'min stake seconds were added while staked')
+    assert(await tryUpdatePool({ endDate: new BN(Math.floor(Date.now() / 1000) - 10) }) == 'InvalidEndDate', 'pool was ended while staked')
+
+    // shortening the cooldown only releases tokens sooner
+    assert(await tryUpdatePool({ cooldownSeconds: 5 }) == null, 'cooldown could not be shortened while staked')
+    const poolAcct = await program.account.stakePool.fetch(breadPool, "confirmed")
+    assert(poolAcct.cooldownSeconds == 5, 'cooldown was not shortened')
+  })
+
+  it('Reject a receipt mint from the wallet that only paid for the entry', async () => {
+    const receiptPool = await setupAllowlistPool()
+    const entryPayer = Keypair.generate()
+    await safeAirdrop(entryPayer.publicKey, connection)
+    const mint = await createNFTMint(connection, nftAuthority, provider.wallet.publicKey)
+    const [metadata] = await createMasterEditionTxs(mint, nftAuthority, connection)
+
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), receiptPool.toBytes(), mint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+    await program.methods.initEntry(provider.wallet.publicKey, null)
+    .accounts({
+      stakeEntry: stakeEntry,
+      stakePool: receiptPool,
+      originalMint: mint,
+      originalMintMetadata: metadata,
+      payer: entryPayer.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([entryPayer])
+    .rpc()
+
+    const [stakeMint, stakeMintBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_MINT_SEED), stakeEntry.toBuffer()],
+      program.programId
+    )
+    const [stakeMintMetadata, stakeMintMetadataBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), stakeMint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+
+    let stakeMintError = null
+    try {
+      await program.methods.initStakeMint()
+      .accounts({
+        stakeEntry: stakeEntry,
+        stakePool: receiptPool,
+        originalMint: mint,
+        originalMintMetadata: metadata,
+        stakeMint: stakeMint,
+        stakeMintMetadata: stakeMintMetadata,
+        programAuthority: programAuthority,
+        payer: entryPayer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        metadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY
+      })
+      .signers([entryPayer])
+      .rpc()
+    } catch (e) {
+      stakeMintError = e
+    }
+    assert(stakeMintError != null && stakeMintError.error.errorCode.code == 'InvalidAuthority', 'the entry payer attached a receipt mint')
+  })
+
+  it('Reassign stake entry to another pool', async () => {
+    const { breadMint, breadPool, stakeEntry, escrowTokenAccount, accounts } = await setupFungiblePool()
+    await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    const { rewardDistributor, rewardMint, distributorRewardAta } = await setupRewardDistributor(breadPool, 0)
+    const userRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, rewardMint, provider.wallet.publicKey)
+
+    await delay(2000)
+    const claimTx = await program.methods.claimRewards()
+    .accounts({
+      rewardDistributor: rewardDistributor,
+      rewardDistributorTokenAccount: distributorRewardAta,
+      rewardMint: rewardMint,
+      stakePool: breadPool,
+      stakeEntry: stakeEntry,
+      originalMint: breadMint,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: accounts.userOriginalMintTokenAccount,
+      userRewardMintTokenAccount: userRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    })
+    .rpc()
+    await connection.confirmTransaction(claimTx, "confirmed")
+
+    // the new pool is keyed by another mint and has the same authority
+    const newPoolMint = await createMint(connection, chiefBreadHead, chiefBreadHead.publicKey, null, 0)
+    const [newPool, newPoolBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_POOL_SEED), newPoolMint.toBuffer()],
+      program.programId
+    )
+    const [newPoolStats, newPoolStatsBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(POOL_STATS_SEED), newPool.toBuffer()],
+      program.programId
+    )
+    const [newStakeEntry, newEntryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), newPool.toBytes(), breadMint.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    )
+    await program.methods.initPool({
+      requiresCollections: [],
+      requiresAuthorization: false,
+      authorizationMerkleRoot: null,
+      authority: chiefBreadHead.publicKey,
+      resetOnStake: false,
+      cooldownSeconds: null,
+      minStakeSeconds: null,
+      endDate: null,
+      restLevels: [new BN(1), new BN(2), new BN(3), new BN(4), new BN(5)],
+      progressMode: 0,
+      progressDecayLevels: 0,
+      emergencyForfeitBps: 0,
+      emergencyLevelDrop: 0,
+      stakeMode: 1,
+    })
+    .accounts({
+      stakePool: newPool,
+      poolStats: newPoolStats,
+      originalMint: newPoolMint,
+      programConfig: programConfig,
+      authority: chiefBreadHead.publicKey,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    const reassignAccounts = {
+      stakeEntry: stakeEntry,
+      newStakeEntry: newStakeEntry,
+      stakePool: breadPool,
+      newStakePool: newPool,
+      originalMint: breadMint,
+      stakeEscrow: escrowTokenAccount,
+      stakeState: accounts.stakeState,
+      programAuthority: accounts.programAuthority,
+      authority: chiefBreadHead.publicKey,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId
+    }
+
+    // a staked entry cannot be reassigned
+    let stakedError = null
+    try {
+      await program.methods.reassignStakeEntry({ user: provider.wallet.publicKey })
+      .accounts(reassignAccounts)
+      .signers([chiefBreadHead])
+      .rpc()
+    } catch (e) {
+      stakedError = e
+    }
+    assert(stakedError != null && stakedError.error.errorCode.code == 'StakeEntryAlreadyStaked', 'a staked entry was reassigned')
+
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+
+    // only the authority of both pools can reassign the entry
+    const attacker = Keypair.generate()
+    await safeAirdrop(attacker.publicKey, connection)
+    let authorityError = null
+    try {
+      await program.methods.reassignStakeEntry({ user: provider.wallet.publicKey })
+      .accounts({ ...reassignAccounts, authority: attacker.publicKey })
+      .signers([attacker])
+      .rpc()
+    } catch (e) {
+      authorityError = e
+    }
+    assert(authorityError != null && authorityError.error.errorCode.code == 'InvalidPoolAuthority', 'another wallet reassigned the entry')
+
+    const entryBefore = await program.account.stakeEntry.fetch(stakeEntry, "confirmed")
+    const tx = await program.methods.reassignStakeEntry({ user: provider.wallet.publicKey })
+    .accounts(reassignAccounts)
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(tx, "confirmed")
+
+    assert(await connection.getAccountInfo(stakeEntry, "confirmed") == null, 'old stake entry was not closed')
+    assert(await connection.getAccountInfo(escrowTokenAccount, "confirmed") == null, 'old stake escrow was not closed')
+    const newEntry = await program.account.stakeEntry.fetch(newStakeEntry, "confirmed")
+    assert(newEntry.pool.toBase58() == newPool.toBase58(), 'stake entry pool does not match')
+    assert(newEntry.totalStakeSeconds.eq(entryBefore.totalStakeSeconds), 'stake seconds were not carried')
+    assert(newEntry.rewardSecondsClaimed.eq(entryBefore.rewardSecondsClaimed), 'paid stake seconds were not carried')
+
+    // the new pool only pays the carried seconds that were not paid yet
+    const newRewards = await setupRewardDistributor(newPool, 0)
+    const newUserRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, newRewards.rewardMint, provider.wallet.publicKey)
+    const newClaimTx = await program.methods.claimRewards()
+    .accounts({
+      rewardDistributor: newRewards.rewardDistributor,
+      rewardDistributorTokenAccount: newRewards.distributorRewardAta,
+      rewardMint: newRewards.rewardMint,
+      stakePool: newPool,
+      stakeEntry: newStakeEntry,
+      originalMint: breadMint,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: accounts.userOriginalMintTokenAccount,
+      userRewardMintTokenAccount: newUserRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    })
+    .rpc()
+    await connection.confirmTransaction(newClaimTx, "confirmed")
+    const newRewardAccount = await getAccount(provider.connection, newUserRewardAta, "confirmed")
+    const unpaid = entryBefore.totalStakeSeconds.sub(entryBefore.rewardSecondsClaimed)
+    assert(newRewardAccount.amount == BigInt(unpaid.toString()), 'carried stake seconds were paid again')
+  })
+
+  it('Reject reassigning an entry with a receipt mint or preserved progress', async () => {
+    const newPool = await setupAllowlistPool()
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+    const tryReassign = async (stakePoolId: PublicKey, stakeEntry: PublicKey, mint: PublicKey, stakeSeed: PublicKey, stakeState: PublicKey) => {
+      const [newStakeEntry, newEntryBump] = await PublicKey.findProgramAddress(
+        [Buffer.from(STAKE_ENTRY_SEED), newPool.toBytes(), mint.toBuffer(), stakeSeed.toBuffer()],
+        program.programId
+      )
+      const [stakeEscrow, stakeEscrowBump] = await PublicKey.findProgramAddress(
+        [Buffer.from(STAKE_ESCROW_SEED), stakeEntry.toBuffer()],
+        program.programId
+      )
+      try {
+        await program.methods.reassignStakeEntry({ user: provider.wallet.publicKey })
+        .accounts({
+          stakeEntry: stakeEntry,
+          newStakeEntry: newStakeEntry,
+          stakePool: stakePoolId,
+          newStakePool: newPool,
+          originalMint: mint,
+          stakeEscrow: stakeEscrow,
+          stakeState: stakeState,
+          programAuthority: programAuthority,
+          authority: chiefBreadHead.publicKey,
+          payer: chiefBreadHead.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([chiefBreadHead])
+        .rpc()
+      } catch (e) {
+        return e.error.errorCode.code
+      }
+      return null
+    }
+
+    // progress kept by a preserve progress pool would be left behind
+    const { breadMint, breadPool, stakeEntry, accounts } = await setupFungiblePool({ progressMode: 1 })
+    await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+    assert(await connection.getAccountInfo(accounts.stakeState, "confirmed") != null, 'stake state was closed')
+    assert(await tryReassign(breadPool, stakeEntry, breadMint, provider.wallet.publicKey, accounts.stakeState) == 'CannotReassignStakeEntry', 'entry with preserved progress was reassigned')
+
+    // the receipt mint is seeded by the old entry
+    const receiptPool = await setupAllowlistPool()
+    const mint = await createNFTMint(connection, nftAuthority, provider.wallet.publicKey)
+    const [metadata] = await createMasterEditionTxs(mint, nftAuthority, connection)
+    assert(await tryInitEntry(receiptPool, mint, null) == null, 'stake entry was not created')
+    const [receiptEntry, receiptEntryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), receiptPool.toBytes(), mint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+    const [stakeMint, stakeMintBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_MINT_SEED), receiptEntry.toBuffer()],
+      program.programId
+    )
+    const [stakeMintMetadata, stakeMintMetadataBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(metadataSeed), METADATA_PROGRAM_ID.toBuffer(), stakeMint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )
+    const stakeMintTx = await program.methods.initStakeMint()
+    .accounts({
+      stakeEntry: receiptEntry,
+      stakePool: receiptPool,
+      originalMint: mint,
+      originalMintMetadata: metadata,
+      stakeMint: stakeMint,
+      stakeMintMetadata: stakeMintMetadata,
+      programAuthority: programAuthority,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(stakeMintTx, "confirmed")
+    const [receiptStakeState, receiptStakeStateBump] = await PublicKey.findProgramAddress(
+      [PublicKey.default.toBuffer(), receiptPool.toBuffer(), mint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+    assert(await tryReassign(receiptPool, receiptEntry, mint, PublicKey.default, receiptStakeState) == 'CannotReassignStakeEntry', 'entry with a receipt mint was reassigned')
+  })
+
+  it('Reject closing a stake pool with staked entries', async () => {
+    const { breadPool, breadPoolStats, accounts } = await setupFungiblePool()
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx, "confirmed")
+
+    const [rewardDistributor, distributorBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(REWARD_DISTRIBUTOR_SEED), breadPool.toBuffer()],
+      program.programId
+    )
+    let closeError = null
+    try {
+      await program.methods.closeStakePool()
+      .accounts({
+        stakePool: breadPool,
+        poolStats: breadPoolStats,
+        rewardDistributor: rewardDistributor,
+        authority: chiefBreadHead.publicKey,
+      })
+      .signers([chiefBreadHead])
+      .rpc()
+    } catch (e) {
+      closeError = e
+    }
+    assert(closeError != null && closeError.error.errorCode.code == 'CannotClosePoolWithStakedEntries', 'stake pool was closed with staked entries')
+  })
+
+  it('Reject closing a staked entry', async () => {
+    const { breadPool, stakeEntry, escrowTokenAccount, accounts } = await setupFungiblePool()
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx, "confirmed")
+
+    let closeError = null
+    try {
+      await program.methods.closeStakeEntry()
+      .accounts({
+        stakeEntry: stakeEntry,
+        stakePool: breadPool,
+        stakeEscrow: escrowTokenAccount,
+        programAuthority: accounts.programAuthority,
+        authority: chiefBreadHead.publicKey,
+        payer: chiefBreadHead.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([chiefBreadHead])
+      .rpc()
+    } catch (e) {
+      closeError = e
+    }
+    assert(closeError != null && closeError.error.errorCode.code == 'CannotCloseStakedEntry', 'a staked entry was closed')
+  })
+
+  it('Stake, unstake and close an nft entry in escrow mode', async () => {
+    const escrowPool = await setupAllowlistPool({ stakeMode: 1 })
+    const mint = await createNFTMint(connection, nftAuthority, provider.wallet.publicKey)
+    const [metadata, masterEdition] = await createMasterEditionTxs(mint, nftAuthority, connection)
+    assert(await tryInitEntry(escrowPool, mint, null) == null, 'stake entry was not created')
+
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), escrowPool.toBytes(), mint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+    const [escrowTokenAccount, escrowBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ESCROW_SEED), stakeEntry.toBuffer()],
+      program.programId
+    )
+    const [escrowPoolStats, escrowPoolStatsBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(POOL_STATS_SEED), escrowPool.toBuffer()],
+      program.programId
+    )
+    const [escrowStakerProfile, escrowStakerProfileBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKER_PROFILE_SEED), escrowPool.toBuffer(), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    )
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+    const [stakeState, stateBump] = await PublicKey.findProgramAddress(
+      [provider.wallet.publicKey.toBuffer(), escrowPool.toBuffer(), mint.toBuffer(), Buffer.from("state")],
+      program.programId
+    )
+    const userAta = await getAssociatedTokenAddress(mint, provider.wallet.publicKey)
+
+    await program.methods.initStakeEscrow()
+    .accounts({
+      stakePool: escrowPool,
+      stakeEntry: stakeEntry,
+      originalMint: mint,
+      escrowTokenAccount: escrowTokenAccount,
+      programAuthority: programAuthority,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    const accounts = {
+      stakeEntry: stakeEntry,
+      stakePool: escrowPool,
+      poolStats: escrowPoolStats,
+      stakerProfile: escrowStakerProfile,
+      programAuthority: programAuthority,
+      originalMint: mint,
+      masterEdition: masterEdition,
+      originalMintMetadata: metadata,
+      tokenRecord: METADATA_PROGRAM_ID,
+      authorizationRules: METADATA_PROGRAM_ID,
+      authorizationRulesProgram: METADATA_PROGRAM_ID,
+      user: provider.wallet.publicKey,
+      userOriginalMintTokenAccount: userAta,
+      escrowTokenAccount: escrowTokenAccount,
+      stakeState: stakeState,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      metadataProgram: METADATA_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY
+    }
+
+    const stakeTx = await program.methods.stake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(stakeTx, "confirmed")
+    const escrowAccount = await getAccount(provider.connection, escrowTokenAccount, "confirmed")
+    assert(Number(escrowAccount.amount) == 1, 'nft was not moved to the escrow')
+
+    const unstakeTx = await program.methods.unstake(new BN(1))
+    .accounts(accounts)
+    .rpc()
+    await connection.confirmTransaction(unstakeTx, "confirmed")
+    const userAccount = await getAccount(provider.connection, userAta, "confirmed")
+    assert(Number(userAccount.amount) == 1, 'nft was not returned from the escrow')
+
+    const closeTx = await program.methods.closeStakeEntry()
+    .accounts({
+      stakeEntry: stakeEntry,
+      stakePool: escrowPool,
+      stakeEscrow: escrowTokenAccount,
+      programAuthority: programAuthority,
+      authority: chiefBreadHead.publicKey,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(closeTx, "confirmed")
+
+    assert(await connection.getAccountInfo(stakeEntry, "confirmed") == null, 'stake entry was not closed')
+    assert(await connection.getAccountInfo(escrowTokenAccount, "confirmed") == null, 'stake escrow was not closed')
+  })
+
+  it('Close stake entry', async () => {
+    const [stakeEntry, entryBump] = await PublicKey.findProgramAddress(
+      [Buffer.from(STAKE_ENTRY_SEED), stakePool.toBytes(), originalMint.toBuffer(), PublicKey.default.toBuffer()],
+      program.programId
+    )
+
+    const [programAuthority, authBump] = await PublicKey.findProgramAddress(
+      [Buffer.from("authority")],
+      program.programId
+    )
+
+    // freeze mode entries have no escrow to close
+    const tx = await program.methods.closeStakeEntry()
+    .accounts({
+      stakeEntry: stakeEntry,
+      stakePool: stakePool,
+      stakeEscrow: stakeEscrow,
+      programAuthority: programAuthority,
+      authority: chiefBreadHead.publicKey,
+      payer: chiefBreadHead.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const entryInfo = await connection.getAccountInfo(stakeEntry, "confirmed")
+    assert(entryInfo == null, 'stake entry was not closed')
+  })
+
+  it('Close stake pool', async () => {
+    const [rewardDistributor] = await PublicKey.findProgramAddress(
+      [Buffer.from(REWARD_DISTRIBUTOR_SEED), stakePool.toBuffer()],
+      program.programId
+    )
+    const closePoolAccounts = {
+      stakePool: stakePool,
+      poolStats: poolStats,
+      rewardDistributor: rewardDistributor,
+      authority: chiefBreadHead.publicKey,
+    }
+
+    // the pool cannot be closed while its reward distributor is open
+    let closeError = null
+    try {
+      await program.methods.closeStakePool()
+      .accounts(closePoolAccounts)
+      .signers([chiefBreadHead])
+      .rpc()
+    } catch (e) {
+      closeError = e
+    }
+    assert(closeError != null && closeError.error.errorCode.code == 'CannotClosePoolWithRewardDistributor', 'stake pool was closed with an open reward distributor')
+
+    const distributor = await program.account.rewardDistributor.fetch(rewardDistributor)
+    const authorityRewardAta = await createAssociatedTokenAccount(connection, chiefBreadHead, distributor.rewardMint, chiefBreadHead.publicKey)
+    const closeDistributorTx = await program.methods.closeRewardDistributor()
+    .accounts({
+      rewardDistributor: rewardDistributor,
+      rewardDistributorTokenAccount: await getAssociatedTokenAddress(distributor.rewardMint, rewardDistributor, true),
+      rewardMint: distributor.rewardMint,
+      stakePool: stakePool,
+      authority: chiefBreadHead.publicKey,
+      authorityRewardMintTokenAccount: authorityRewardAta,
+      tokenProgram: TOKEN_PROGRAM_ID
+    })
+    .signers([chiefBreadHead])
+    .rpc()
+    await connection.confirmTransaction(closeDistributorTx, "confirmed")
+
+    const tx = await program.methods.closeStakePool()
+    .accounts(closePoolAccounts)
+    .signers([chiefBreadHead])
+    .rpc()
+
+    await connection.confirmTransaction(tx)
+
+    const poolInfo = await connection.getAccountInfo(stakePool, "confirmed")
+    assert(poolInfo == null, 'stake pool was not closed')
   })
 })